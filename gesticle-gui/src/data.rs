@@ -3,7 +3,8 @@ use gtk::gio::ListStore;
 use gtk::glib;
 
 use gesticle::configuration::GestureActions;
-use gesticle::gestures::{GestureType, PinchDirection, RotationDirection, SwipeDirection};
+use gesticle::gestures::{GestureType, PinchDirection, RingDirection, RotationDirection, ScreenEdge, StripDirection,
+                         StrokeDirection, SwipeDirection, Switch};
 
 mod imp {
     use std::cell::RefCell;
@@ -83,7 +84,7 @@ glib::wrapper! {
 impl GestureSetting {
     pub fn new_cfg(gesture_type: &GestureType, app: Option<&str>, config: &GestureActions) -> GestureSetting {
         let setting = &gesture_type.to_config();
-        let inherited = config.get_for_app(setting, None);
+        let inherited = config.get_first_for_app(&gesture_type.to_configs(), None);
 
         let action = if config.is_specified(setting, app) {
             config.get_for_app(setting, app)
@@ -125,17 +126,43 @@ impl GestureSetting {
 
     fn gesture_direction(gesture_type: &GestureType) -> String {
         match gesture_type {
-            GestureType::Swipe(direction, _) => direction.to_string(),
-            GestureType::Rotation(direction, _) => direction.to_string(),
-            GestureType::Pinch(direction, _) => direction.to_string()
+            GestureType::Swipe(direction, _, _, _) => direction.to_string(),
+            GestureType::Rotation(direction, _, _) => direction.to_string(),
+            GestureType::Pinch(direction, _, _) => direction.to_string(),
+            GestureType::Hold(fingers, _) => format!("{} fingers", fingers),
+            GestureType::Sequence(sequence) => sequence.to_owned(),
+            GestureType::Edge(edge, _) => format!("from {}", edge),
+            GestureType::Stroke(directions) =>
+                directions.iter().map(|d| d.to_string().to_uppercase()).collect::<Vec<String>>().join("-"),
+            GestureType::Modified(_, gesture) => Self::gesture_direction(gesture),
+            GestureType::Switch(switch, on) => format!("{} {}", switch, switch.state(*on)),
+            GestureType::PadButton(button) => format!("button {}", button),
+            GestureType::PadRing(direction, _) => direction.to_string(),
+            GestureType::PadStrip(direction, _) => direction.to_string()
         }
     }
 
     fn gesture_category(gesture_type: &GestureType, app: Option<&str>) -> String {
         let mut category = match gesture_type {
-            GestureType::Swipe(_, fingers) => format!("{} fingers Swipes", fingers),
-            GestureType::Rotation(_, _) => "Rotations".to_owned(),
-            GestureType::Pinch(_, _) => "Pinches".to_owned()
+            GestureType::Swipe(_, fingers, _, _) => format!("{} fingers Swipes", fingers),
+            GestureType::Rotation(_, _, fingers) if *fingers > 2 => format!("{} fingers Rotations", fingers),
+            GestureType::Rotation(_, _, _) => "Rotations".to_owned(),
+            GestureType::Pinch(_, _, fingers) if *fingers > 2 => format!("{} fingers Pinches", fingers),
+            GestureType::Pinch(_, _, _) => "Pinches".to_owned(),
+            GestureType::Hold(_, _) => "Holds".to_owned(),
+            GestureType::Sequence(_) => "Sequences".to_owned(),
+            GestureType::Edge(_, fingers) => format!("{} fingers Touchscreen Edge Swipes", fingers),
+            GestureType::Stroke(_) => "Mouse Strokes".to_owned(),
+            GestureType::Switch(_, _) => "Switches".to_owned(),
+            GestureType::PadButton(_) => "Tablet Pad Buttons".to_owned(),
+            GestureType::PadRing(_, ring) if *ring > 0 => format!("Tablet Pad Ring {}", ring),
+            GestureType::PadRing(_, _) => "Tablet Pad Ring".to_owned(),
+            GestureType::PadStrip(_, strip) if *strip > 0 => format!("Tablet Pad Strip {}", strip),
+            GestureType::PadStrip(_, _) => "Tablet Pad Strip".to_owned(),
+            GestureType::Modified(modifiers, gesture) => {
+                let modifiers: Vec<String> = modifiers.iter().map(|m| format!("{:?}", m)).collect();
+                return format!("{} {}", modifiers.join("+"), Self::gesture_category(gesture, app));
+            }
         };
 
         if let Some(context) = app {
@@ -145,19 +172,66 @@ impl GestureSetting {
     }
 
     pub fn create_app_data(store: &ListStore, app: Option<&str>, config: &GestureActions) {
-        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Up, 3), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Down, 3), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Left, 3), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Right, 3), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Up, 4), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Down, 4), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Left, 4), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Right, 4), app, config));
-
-        store.append(&GestureSetting::new_cfg(&GestureType::Pinch(PinchDirection::In, 0.0), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Pinch(PinchDirection::Out, 0.0), app, config));
-
-        store.append(&GestureSetting::new_cfg(&GestureType::Rotation(RotationDirection::Left, 0.0), app, config));
-        store.append(&GestureSetting::new_cfg(&GestureType::Rotation(RotationDirection::Right, 0.0), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Up, 3, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Down, 3, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Left, 3, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Right, 3, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::UpLeft, 3, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::UpRight, 3, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::DownLeft, 3, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::DownRight, 3, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Up, 4, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Down, 4, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Left, 4, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::Right, 4, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::UpLeft, 4, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::UpRight, 4, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::DownLeft, 4, None, None), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Swipe(SwipeDirection::DownRight, 4, None, None), app, config));
+
+        store.append(&GestureSetting::new_cfg(&GestureType::Pinch(PinchDirection::In, 0.0, 2), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Pinch(PinchDirection::Out, 0.0, 2), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Pinch(PinchDirection::In, 0.0, 3), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Pinch(PinchDirection::Out, 0.0, 3), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Pinch(PinchDirection::In, 0.0, 4), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Pinch(PinchDirection::Out, 0.0, 4), app, config));
+
+        store.append(&GestureSetting::new_cfg(&GestureType::Rotation(RotationDirection::Left, 0.0, 2), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Rotation(RotationDirection::Right, 0.0, 2), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Rotation(RotationDirection::Left, 0.0, 3), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Rotation(RotationDirection::Right, 0.0, 3), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Rotation(RotationDirection::Left, 0.0, 4), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Rotation(RotationDirection::Right, 0.0, 4), app, config));
+
+        store.append(&GestureSetting::new_cfg(&GestureType::Hold(3, 0), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Hold(4, 0), app, config));
+
+        store.append(&GestureSetting::new_cfg(&GestureType::Edge(ScreenEdge::Left, 1), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Edge(ScreenEdge::Right, 1), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Edge(ScreenEdge::Top, 1), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Edge(ScreenEdge::Bottom, 1), app, config));
+
+        store.append(&GestureSetting::new_cfg(&GestureType::Stroke(vec![StrokeDirection::Left]), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Stroke(vec![StrokeDirection::Right]), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Stroke(vec![StrokeDirection::Up]), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Stroke(vec![StrokeDirection::Down]), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Stroke(vec![StrokeDirection::Up, StrokeDirection::Right]),
+                                              app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Stroke(vec![StrokeDirection::Down, StrokeDirection::Left]),
+                                              app, config));
+
+        store.append(&GestureSetting::new_cfg(&GestureType::Switch(Switch::Lid, true), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Switch(Switch::Lid, false), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Switch(Switch::Tablet, true), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::Switch(Switch::Tablet, false), app, config));
+
+        store.append(&GestureSetting::new_cfg(&GestureType::PadButton(0), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::PadButton(1), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::PadButton(2), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::PadButton(3), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::PadRing(RingDirection::Cw, 0), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::PadRing(RingDirection::Ccw, 0), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::PadStrip(StripDirection::Up, 0), app, config));
+        store.append(&GestureSetting::new_cfg(&GestureType::PadStrip(StripDirection::Down, 0), app, config));
     }
 }
@@ -1,6 +1,6 @@
 mod data;
 
-use std::collections::HashMap;
+use std::path::PathBuf;
 use gtk::prelude::*;
 use gtk::{Align, Application, ApplicationWindow, Builder, Button, ButtonsType, Dialog, DialogFlags, Entry, gdk, glib, Inhibit, ListBox, MessageDialog, MessageType, Orientation, ResponseType, SearchBar, SearchEntry, ToggleButton};
 use gtk::gio::ListStore;
@@ -9,11 +9,27 @@ use log::{error, info};
 use gesticle::configuration::{GestureActions, home_path, init_logging};
 
 use std::rc::Rc;
+use toml::Value;
+use toml::value::Table;
 use gdk::ModifierType;
 use gtk::builders::{BoxBuilder, EntryBuilder, LabelBuilder, ListBoxRowBuilder, SwitchBuilder};
 use gesticle::dbus;
+use gesticle::gestures::swipe_angle_tolerance;
 use crate::data::GestureSetting;
 
+// settings shown in the settings tab: configuration key and id of the entry editing it
+const SETTINGS: [(&str, &str); 9] = [
+    ("gesture.trigger.pinch.out.scale", "pinch_out_trigger"),
+    ("gesture.trigger.pinch.in.scale", "pinch_in_trigger"),
+    ("gesture.trigger.rotation.angle", "rotation_angle"),
+    ("gesture.trigger.swipe.angle_tolerance", "swipe_angle_tolerance"),
+    ("gesture.trigger.swipe.min_distance", "swipe_min_distance"),
+    ("gesture.trigger.swipe.fast_velocity", "swipe_fast_velocity"),
+    ("gesture.trigger.swipe.long_distance", "swipe_long_distance"),
+    ("gesture.trigger.hold.min_duration", "hold_min_duration"),
+    ("gesture.trigger.cooldown.default", "cooldown"),
+];
+
 struct GesticleGui {
     actions: Rc<GestureActions>,
     data_store: ListStore,
@@ -26,23 +42,28 @@ struct GesticleGui {
     search_bar: SearchBar,
     list: ListBox,
     manual_input_button: ToggleButton,
-    pinch_out_trigger_entry: Entry,
-    pinch_in_trigger_entry: Entry,
-    setting_pinch_out_trigger_scale: Object,
-    setting_pinch_in_trigger_scale: Object,
+    settings: Vec<(Entry, Object)>,
 }
 
 impl GesticleGui {
     fn from_builder(builder: &Builder, actions: GestureActions, application: &gtk::Application) -> GesticleGui {
 
-        let pinch_in_value = match actions.get_float("gesture.trigger.pinch.in.scale") {
-            Some(t) => Some(t.to_string()),
-            None => None
-        };
-        let pinch_out_value = match actions.get_float("gesture.trigger.pinch.out.scale") {
-            Some(t) => Some(t.to_string()),
-            None => None
-        };
+        let settings = SETTINGS.iter()
+            .map(|(config, entry)| {
+                let entry = builder.object::<Entry>(entry).expect("no settings entry found");
+                let value = actions.get_float(config).map(|v| v.to_string());
+                let setting = GestureSetting::new(
+                    config.to_string(),
+                    "".to_owned(),
+                    "settings".to_owned(),
+                    None,
+                    value,
+                    None,
+                    true,
+                ).upcast::<Object>();
+                (entry, setting)
+            })
+            .collect();
 
         let gui = GesticleGui {
             actions: Rc::new(actions),
@@ -56,26 +77,7 @@ impl GesticleGui {
             search_bar: builder.object("search_bar").expect("no search bar"),
             list: builder.object("listbox").expect("no listbox"),
             manual_input_button: builder.object("manual_input").expect("no manual input toggle"),
-            pinch_out_trigger_entry: builder.object("pinch_out_trigger").unwrap(),
-            pinch_in_trigger_entry: builder.object("pinch_in_trigger").unwrap(),
-            setting_pinch_in_trigger_scale: GestureSetting::new(
-                "gesture.trigger.pinch.in.scale".to_owned(),
-                "".to_owned(),
-                "settings".to_owned(),
-                None,
-                pinch_in_value,
-                None,
-                true,
-            ).upcast::<Object>(),
-            setting_pinch_out_trigger_scale: GestureSetting::new(
-                "gesture.trigger.pinch.out.scale".to_owned(),
-                "".to_owned(),
-                "settings".to_owned(),
-                None,
-                pinch_out_value,
-                None,
-                true,
-            ).upcast::<Object>(),
+            settings,
         };
 
         gui.window.set_application(Some(application));
@@ -132,13 +134,20 @@ impl GesticleGui {
     // Save current actions to filesystem and let gesticled (daemon) know to re-load configuration
     fn save(
         model: &ListStore,
-        setting_pinch_in_trigger_scale: &Object,
-        setting_pinch_out_trigger_scale: &Object,
+        settings: &[Object],
         window: &ApplicationWindow,
     ) {
-        let mut actions = HashMap::new();
-
-        let append_item = |actions: &mut HashMap<String, HashMap<String, String>>, item: &Object| {
+        // start from the file on disk so settings without a row in the GUI (sequences, input and device
+        // sections, cooldown overrides...) survive the save
+        let path = home_path(".gesticle/config.toml")
+            .filter(|p| p.exists())
+            .unwrap_or_else(|| PathBuf::from("/etc/gesticle/config.toml"));
+        let mut actions = std::fs::read_to_string(&path).ok()
+            .and_then(|s| s.parse::<Value>().map_err(|e| error!("ignoring unreadable configuration {:?}: {}", path, e)).ok())
+            .filter(Value::is_table)
+            .unwrap_or_else(|| Value::Table(Table::new()));
+
+        let append_item = |actions: &mut Value, item: &Object, parse: fn(String) -> Value| {
             let config = item.property::<Option<String>>("config")
                 .expect("config property");
 
@@ -149,23 +158,33 @@ impl GesticleGui {
                 Some("".to_owned())
             };
 
-            if let Some(value) = action.filter(|s| !s.is_empty()) {
-                let mut parts = config.split('.');
-                let key = parts.next_back().unwrap().to_owned();
-                let table = parts.collect::<Vec<&str>>().join(".");
+            let mut parts = config.split('.');
+            let key = parts.next_back().unwrap().to_owned();
 
-                let table_actions = actions.entry(table).or_insert(HashMap::new());
-                (*table_actions).insert(key, value);
+            let mut table = actions.as_table_mut().unwrap();
+            for part in parts {
+                let entry = table.entry(part.to_owned()).or_insert_with(|| Value::Table(Table::new()));
+                if !entry.is_table() {
+                    *entry = Value::Table(Table::new());
+                }
+                table = entry.as_table_mut().unwrap();
             }
+
+            match action.filter(|s| !s.is_empty()) {
+                Some(value) => table.insert(key, parse(value)),
+                None => table.remove(&key),
+            };
         };
 
         for index in 0..model.n_items() {
             let item = model.item(index as u32).expect("no item on existing row");
-            append_item(&mut actions, &item);
+            append_item(&mut actions, &item, Value::String);
         }
 
-        append_item(&mut actions, &setting_pinch_out_trigger_scale);
-        append_item(&mut actions, &setting_pinch_in_trigger_scale);
+        for setting in settings {
+            Self::clamp_setting(setting);
+            append_item(&mut actions, setting, |v| v.parse::<f64>().map(Value::Float).unwrap_or(Value::String(v)));
+        }
 
         let s = toml::to_string_pretty(&actions).unwrap();
 
@@ -196,6 +215,19 @@ impl GesticleGui {
         }
     }
 
+    // Bring settings within their range, showing the value that is saved in its entry
+    fn clamp_setting(setting: &Object) {
+        let config = setting.property::<Option<String>>("config").expect("config property");
+        let value = setting.property::<Option<String>>("action").and_then(|v| v.parse::<f64>().ok());
+
+        if let ("gesture.trigger.swipe.angle_tolerance", Some(value)) = (config.as_str(), value) {
+            let clamped = swipe_angle_tolerance(value);
+            if clamped != value {
+                setting.set_property("action", clamped.to_string());
+            }
+        }
+    }
+
     fn bind_data(&self) {
         self.list.bind_model(Some(&self.data_store), clone!(@strong self.manual_input_button as manual_input_button => move |item| {
             let item: &GestureSetting = item.downcast_ref::<GestureSetting>().expect("wrong item type");
@@ -278,12 +310,11 @@ impl GesticleGui {
             GestureSetting::create_app_data(&self.data_store, Some(app.as_str()), &self.actions);
         }
 
-        self.setting_pinch_out_trigger_scale.bind_property("action", &self.pinch_out_trigger_entry, "text")
-            .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
-            .build();
-        self.setting_pinch_in_trigger_scale.bind_property("action", &self.pinch_in_trigger_entry, "text")
-            .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
-            .build();
+        for (entry, setting) in &self.settings {
+            setting.bind_property("action", entry, "text")
+                .flags(glib::BindingFlags::DEFAULT | glib::BindingFlags::SYNC_CREATE | glib::BindingFlags::BIDIRECTIONAL)
+                .build();
+        }
     }
 
     fn connect_gui_events(&self) {
@@ -352,25 +383,30 @@ impl GesticleGui {
                 .contains(s.text().to_lowercase().as_str())
         }))));
 
+        let settings: Vec<Object> = self.settings.iter().map(|(_, setting)| setting.clone()).collect();
         self.save_button.connect_clicked(
-            clone!(@strong self.window as window, @strong self.data_store as model,
-                        @strong self.setting_pinch_out_trigger_scale as setting_pinch_out_trigger_scale,
-                        @strong self.setting_pinch_in_trigger_scale as setting_pinch_in_trigger_scale => move |_| {
-                Self::save(&model, &setting_pinch_in_trigger_scale, &setting_pinch_out_trigger_scale, &window);
+            clone!(@strong self.window as window, @strong self.data_store as model => move |_| {
+                Self::save(&model, &settings, &window);
             })
         );
     }
 }
 
 fn main() {
-    init_logging(false, Some(".gesticle/gesticle-gui.log"));
+    if let Err(e) = init_logging(false, Some(".gesticle/gesticle-gui.log")) {
+        eprintln!("gesticle-gui: {}", e);
+    }
 
     let application = Application::builder().application_id("pt.guedes.gesticle-settings-gui").build();
 
     application.connect_startup(|app| {
         let glade_src = include_str!("../gesticle-settings.glade");
         let builder = Builder::from_string(glade_src);
-        let actions = GestureActions::new(None); // TODO we should allow selecting which file to edit!
+        // TODO we should allow selecting which file to edit!
+        let actions = GestureActions::new(None).unwrap_or_else(|e| {
+            eprintln!("gesticle-gui: {}", e);
+            std::process::exit(1);
+        });
 
         let gui = GesticleGui::from_builder(&builder, actions, app);
 
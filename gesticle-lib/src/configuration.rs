@@ -1,3 +1,4 @@
+use std::collections::HashMap;
 use std::fs::create_dir;
 use std::fs::File;
 use std::path::{Path, PathBuf};
@@ -5,30 +6,36 @@ use std::path::{Path, PathBuf};
 use simplelog::*;
 use config::Source;
 
-// these are the prefixes that are not apps...
-const CONFIGURATION_PREFIXES: [&'static str; 4] = ["swipe", "rotation", "pinch", "gesture"];
+use crate::error::{Error, Result};
 
-pub fn init_logging(debug: bool, relative_path: Option<&str>) {
-    let user_app_home = home_path(".gesticle").expect("cannot find user home");
+// these are the prefixes that are not apps, README.1 lists them as names apps can not have settings under
+const CONFIGURATION_PREFIXES: [&'static str; 16] = ["swipe", "rotation", "pinch", "hold", "sequence", "gesture", "input",
+    "device", "touch", "mouse", "ctrl", "alt", "shift", "super", "switch", "pad"];
+
+pub fn init_logging(debug: bool, relative_path: Option<&str>) -> Result<()> {
+    let user_app_home = home_path(".gesticle").ok_or(Error::NoHome)?;
     if !user_app_home.exists() {
-        create_dir(user_app_home).expect("cannot create app dir in user home");
+        create_dir(&user_app_home)
+            .map_err(|e| Error::Io(format!("cannot create {}", user_app_home.display()), e))?;
     }
 
     let log_path = relative_path.unwrap_or(".gesticle/gesticle.log");
-    let log_path = home_path(log_path).expect("cannot create log file path");
+    let log_path = home_path(log_path).ok_or(Error::NoHome)?;
+    let log_file = File::create(&log_path)
+        .map_err(|e| Error::Io(format!("cannot create log file {}", log_path.display()), e))?;
 
     if debug {
-        CombinedLogger::init(
-            vec![
-                TermLogger::new(LevelFilter::Debug, simplelog::Config::default()).unwrap(),
-                WriteLogger::new(LevelFilter::Debug, simplelog::Config::default(),
-                                 File::create(log_path).unwrap()),
-            ]
-        ).unwrap();
+        let mut loggers: Vec<Box<dyn SharedLogger>> = vec![];
+        // there is no terminal to log to when running in the background
+        if let Some(term) = TermLogger::new(LevelFilter::Debug, simplelog::Config::default()) {
+            loggers.push(term);
+        }
+        loggers.push(WriteLogger::new(LevelFilter::Debug, simplelog::Config::default(), log_file));
+        CombinedLogger::init(loggers)?;
     } else {
-        WriteLogger::init(LevelFilter::Info, simplelog::Config::default(),
-                          File::create(log_path).unwrap()).unwrap();
+        WriteLogger::init(LevelFilter::Info, simplelog::Config::default(), log_file)?;
     }
+    Ok(())
 }
 
 pub fn home_path(relative_path: &str) -> Option<PathBuf> {
@@ -38,31 +45,31 @@ pub fn home_path(relative_path: &str) -> Option<PathBuf> {
     }
 }
 
-fn config_file_path(config_path_override: Option<&str>) -> Result<PathBuf, &str> {
+fn config_file_path(config_path_override: Option<&str>) -> std::result::Result<PathBuf, String> {
     let path_exists = |p: &PathBuf| p.exists();
 
     match config_path_override {
-        Some(o) => Ok(Path::new(o).to_owned()),
+        Some(o) => Some(Path::new(o).to_owned()).filter(path_exists).ok_or(format!("nothing in {}", o)),
         None => {
             home_path(".gesticle/config.toml").
                 filter(path_exists).
                 or(Some(Path::new("/etc/gesticle/config.toml").to_owned())).
                 filter(path_exists).
-                ok_or("nothing in ~/.gesticle/config.toml or /etc/gesticle/config.toml")
+                ok_or("nothing in ~/.gesticle/config.toml or /etc/gesticle/config.toml".to_owned())
         }
     }
 }
 
-fn load_settings(config_path_override: Option<&str>) -> config::Config {
+fn load_settings(config_path_override: Option<&str>) -> Result<config::Config> {
     let config_file_path = config_file_path(config_path_override).
-        expect("config file not found");
+        map_err(Error::ConfigNotFound)?;
 
     info!("loading configuration path: {:?}", config_file_path);
 
     let mut settings = config::Config::new();
-    settings.merge(config::File::from(config_file_path)).unwrap();
+    settings.merge(config::File::from(config_file_path))?;
 
-    settings
+    Ok(settings)
 }
 
 #[derive(Debug)]
@@ -71,10 +78,10 @@ pub struct GestureActions {
 }
 
 impl GestureActions {
-    pub fn new(config_path_override: Option<&str>) -> GestureActions {
-        GestureActions {
-            config: load_settings(config_path_override)
-        }
+    pub fn new(config_path_override: Option<&str>) -> Result<GestureActions> {
+        Ok(GestureActions {
+            config: load_settings(config_path_override)?
+        })
     }
 
     pub fn new_with_config(config: config::Config) -> GestureActions {
@@ -83,8 +90,9 @@ impl GestureActions {
         }
     }
 
-    pub fn reload(&mut self) {
-        self.config = load_settings(None);
+    pub fn reload(&mut self) -> Result<()> {
+        self.config = load_settings(None)?;
+        Ok(())
     }
 
     pub fn apps(&self) -> Option<Vec<String>> {
@@ -129,20 +137,149 @@ impl GestureActions {
     }
 
     pub fn get_for_app(&self, setting: &str, app: Option<&str>) -> Option<String> {
-        app.map_or(self.get(setting), |a| self.get(format!("{}.{}", a, setting).as_str()))
-            .or_else(|| self.get(setting))
+        self.get_first_for_app(&[setting.to_owned()], app)
+    }
+
+    /// Like `get_for_app` but falls back through `settings` (ie: from `swipe.left.short.3` to `swipe.left.3`),
+    /// app specific settings take precedence over more specific settings
+    pub fn get_first_for_app(&self, settings: &[String], app: Option<&str>) -> Option<String> {
+        self.get_first_for(settings, app, None)
+    }
+
+    /// The first of `settings` configured for a gesture made in `app` on `device`. The settings of the app take
+    /// precedence over the ones of the device, which take precedence over the global ones, and any of these over
+    /// more specific settings. A setting set to nothing disables the gesture instead of falling back
+    pub fn get_first_for(&self, settings: &[String], app: Option<&str>, device: Option<&str>) -> Option<String> {
+        let get_first = |prefix: Option<&str>| {
+            settings.iter().find_map(|s| self.get(Self::key_for_app(s.to_owned(), prefix).as_str()))
+        };
+        app.and_then(|a| get_first(Some(a)))
+            .or_else(|| device.and_then(|d| settings.iter().find_map(|s| self.get_device_setting(s, d))))
+            .or_else(|| get_first(None))
             .filter(|v| !v.is_empty())
     }
 
+    /// The action for a sequence of gestures (ie: `swipe.up.3,swipe.left.3`), sequences are keys in the `sequence`
+    /// table since they can't be part of a setting path
+    pub fn get_sequence_for_app(&self, sequence: &str, app: Option<&str>) -> Option<String> {
+        let get = |app: Option<&str>| {
+            self.config.get_table(Self::key_for_app("sequence".to_owned(), app).as_str()).ok()
+                .and_then(|mut sequences| sequences.remove(sequence))
+                .and_then(|action| action.into_str().ok())
+        };
+        app.and_then(|a| get(Some(a))).or_else(|| get(None))
+    }
+
+    /// The sequences of gestures bound to an action in `app`, its own and the global ones it does not disable
+    pub fn sequences_for_app(&self, app: Option<&str>) -> Vec<String> {
+        let mut sequences: Vec<String> = vec![];
+        for table in app.into_iter().map(Some).chain(Some(None)) {
+            if let Ok(table) = self.config.get_table(Self::key_for_app("sequence".to_owned(), table).as_str()) {
+                for (sequence, _) in table {
+                    if !sequences.contains(&sequence) {
+                        sequences.push(sequence);
+                    }
+                }
+            }
+        }
+        sequences.retain(|s| self.get_sequence_for_app(s, app).is_some_and(|action| !action.is_empty()));
+        sequences
+    }
+
     pub fn get_float(&self, key: &str) -> Option<f64> {
         self.config.get_float(key).ok()
     }
+
+    /// A list of strings (ie: `include = ["a", "b"]`), empty when it is not set
+    pub fn get_strings(&self, key: &str) -> Vec<String> {
+        self.config.get_array(key).unwrap_or_default().into_iter()
+            .filter_map(|v| v.into_str().ok())
+            .collect()
+    }
+
+    /// All float settings nested under `key`, with keys relative to it (ie: `swipe.left.3`)
+    pub fn get_floats(&self, key: &str) -> HashMap<String, f64> {
+        let mut floats = HashMap::new();
+        if let Ok(table) = self.config.get_table(key) {
+            collect_floats(&mut floats, None, table);
+        }
+        floats
+    }
+
+    /// A setting of a specific device (ie: `[device."Apple Magic Trackpad".swipe.up]`), device names can't be part of
+    /// a setting path so the `device` table is walked instead. Names are matched ignoring case as configuration keys
+    /// are lowercased
+    fn device_value(&self, setting: &str, device: &str) -> Option<config::Value> {
+        let mut value = self.config.get_table("device").ok()?.remove(&device.to_lowercase())?;
+        for key in setting.split('.') {
+            value = value.into_table().ok()?.remove(key)?;
+        }
+        Some(value)
+    }
+
+    /// A setting of a specific device without falling back to the global one
+    pub fn get_device_setting(&self, setting: &str, device: &str) -> Option<String> {
+        self.device_value(setting, device).and_then(|v| v.into_str().ok())
+    }
+
+    pub fn get_for_device(&self, setting: &str, device: Option<&str>) -> Option<String> {
+        self.get_first_for_device(&[setting.to_owned()], device)
+    }
+
+    /// Like `get_first_for_app` but for the device a gesture was made on
+    pub fn get_first_for_device(&self, settings: &[String], device: Option<&str>) -> Option<String> {
+        self.get_first_for(settings, None, device)
+    }
+
+    /// The (lowercased) names of the devices with settings of their own
+    pub fn devices(&self) -> Vec<String> {
+        self.config.get_table("device")
+            .map(|devices| devices.into_keys().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn get_float_for_device(&self, key: &str, device: Option<&str>) -> Option<f64> {
+        device.and_then(|d| self.device_value(key, d))
+            .and_then(|v| v.into_float().ok())
+            .or_else(|| self.get_float(key))
+    }
+
+    pub fn get_bool_for_device(&self, key: &str, device: Option<&str>) -> Option<bool> {
+        device.and_then(|d| self.device_value(key, d))
+            .and_then(|v| v.into_bool().ok())
+            .or_else(|| self.config.get_bool(key).ok())
+    }
+
+    /// Like `get_floats` with the settings of `device` overriding the global ones
+    pub fn get_floats_for_device(&self, key: &str, device: Option<&str>) -> HashMap<String, f64> {
+        let mut floats = self.get_floats(key);
+        if let Some(table) = device.and_then(|d| self.device_value(key, d)).and_then(|v| v.into_table().ok()) {
+            collect_floats(&mut floats, None, table);
+        }
+        floats
+    }
+}
+
+fn collect_floats(floats: &mut HashMap<String, f64>, prefix: Option<&str>, table: HashMap<String, config::Value>) {
+    for (k, v) in table {
+        let key = match prefix {
+            Some(prefix) => format!("{}.{}", prefix, k),
+            None => k
+        };
+        match v.clone().into_table() {
+            Ok(t) => collect_floats(floats, Some(key.as_str()), t),
+            Err(_) => if let Ok(f) = v.into_float() {
+                floats.insert(key, f);
+            }
+        }
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
     use crate::configuration::GestureActions;
+    use crate::error::Error;
 
     #[test]
     fn it_works() {
@@ -158,4 +295,140 @@ mod tests {
         assert_eq!(actions.get_for_app("swipe.up.3", Some("gedit")), Some("ctrl+t".to_owned()));
         assert_eq!(actions.get_for_app("swipe.up.3", Some("chrome")), None);
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn fallback() {
+        // qualified swipes are written in the same table as the unqualified one
+        let mut config = config::Config::new();
+        config.merge(config::File::from_str(r#"
+            [swipe.left]
+            3 = "alt+Left"
+            long.3 = "ctrl+w"
+
+            [firefox.swipe.left]
+            3 = "ctrl+Left"
+
+            [chrome.swipe.left]
+            long.3 = ""
+        "#, config::FileFormat::Toml)).unwrap();
+
+        let actions = GestureActions::new_with_config(config);
+        let long = vec!["swipe.left.long.3".to_owned(), "swipe.left.3".to_owned()];
+        let short = vec!["swipe.left.short.3".to_owned(), "swipe.left.3".to_owned()];
+
+        assert_eq!(actions.get_first_for_app(&long, None), Some("ctrl+w".to_owned()));
+        assert_eq!(actions.get_first_for_app(&short, None), Some("alt+Left".to_owned()));
+        assert_eq!(actions.get_first_for_app(&long, Some("firefox")), Some("ctrl+Left".to_owned()));
+        assert_eq!(actions.get_first_for_app(&long, Some("chrome")), None);
+        assert_eq!(actions.get_first_for_app(&short, Some("chrome")), Some("alt+Left".to_owned()));
+    }
+
+    #[test]
+    fn sequences() {
+        let mut config = config::Config::new();
+        config.merge(config::File::from_str(r#"
+            [sequence]
+            "swipe.up.3,swipe.left.3" = "super+l"
+
+            [firefox.sequence]
+            "swipe.up.3,swipe.left.3" = "ctrl+l"
+            "swipe.down.3,swipe.down.3" = "ctrl+r"
+        "#, config::FileFormat::Toml)).unwrap();
+
+        let actions = GestureActions::new_with_config(config);
+        let sequences = |app| {
+            let mut sequences = actions.sequences_for_app(app);
+            sequences.sort();
+            sequences
+        };
+
+        assert_eq!(sequences(Some("firefox")), vec!["swipe.down.3,swipe.down.3", "swipe.up.3,swipe.left.3"]);
+        assert_eq!(sequences(Some("gedit")), vec!["swipe.up.3,swipe.left.3"]);
+        assert_eq!(sequences(None), vec!["swipe.up.3,swipe.left.3"]);
+        assert_eq!(actions.get_sequence_for_app("swipe.up.3,swipe.left.3", None), Some("super+l".to_owned()));
+        assert_eq!(actions.get_sequence_for_app("swipe.up.3,swipe.left.3", Some("firefox")), Some("ctrl+l".to_owned()));
+        assert_eq!(actions.get_sequence_for_app("swipe.up.3,swipe.left.3", Some("gedit")), Some("super+l".to_owned()));
+        assert_eq!(actions.get_sequence_for_app("swipe.down.3,swipe.down.3", None), None);
+    }
+
+    #[test]
+    fn floats() {
+        let mut config = config::Config::new();
+        config.set("gesture.trigger.continuous.swipe.left.3", 100.0).unwrap();
+        config.set("gesture.trigger.continuous.swipe.up.4", 50.0).unwrap();
+
+        let actions = GestureActions::new_with_config(config);
+        let floats = actions.get_floats("gesture.trigger.continuous");
+
+        assert_eq!(floats.len(), 2);
+        assert_eq!(floats.get("swipe.left.3"), Some(&100.0));
+        assert_eq!(floats.get("swipe.up.4"), Some(&50.0));
+        assert!(actions.get_floats("gesture.trigger.nothing").is_empty());
+    }
+
+    #[test]
+    fn devices() {
+        let mut config = config::Config::new();
+        config.merge(config::File::from_str(r#"
+            [swipe.left]
+            3 = "alt+Left"
+            4 = "super+Left"
+
+            [gesture.trigger.swipe]
+            min_distance = 50.0
+
+            [device."Apple Magic Trackpad".swipe.left]
+            3 = "ctrl+Left"
+            4 = ""
+
+            [device."Apple Magic Trackpad".gesture.trigger.swipe]
+            min_distance = 100.0
+        "#, config::FileFormat::Toml)).unwrap();
+
+        let actions = GestureActions::new_with_config(config);
+        let trackpad = Some("Apple Magic Trackpad");
+
+        assert_eq!(actions.devices(), vec!["apple magic trackpad"]);
+        assert_eq!(actions.apps(), Some(vec![]));
+        assert_eq!(actions.get_for_device("swipe.left.3", trackpad), Some("ctrl+Left".to_owned()));
+        assert_eq!(actions.get_for_device("swipe.left.3", Some("SynPS/2 Synaptics TouchPad")), Some("alt+Left".to_owned()));
+        assert_eq!(actions.get_for_device("swipe.left.3", None), Some("alt+Left".to_owned()));
+        assert_eq!(actions.get_for_device("swipe.left.4", trackpad), None);
+        assert_eq!(actions.get_device_setting("swipe.left.4", "apple magic trackpad"), Some("".to_owned()));
+        assert_eq!(actions.get_float_for_device("gesture.trigger.swipe.min_distance", trackpad), Some(100.0));
+        assert_eq!(actions.get_float_for_device("gesture.trigger.swipe.min_distance", None), Some(50.0));
+        assert_eq!(actions.get_floats_for_device("gesture.trigger.swipe", trackpad).get("min_distance"), Some(&100.0));
+    }
+
+    #[test]
+    fn app_and_device() {
+        let mut config = config::Config::new();
+        config.merge(config::File::from_str(r#"
+            [swipe.left]
+            3 = "alt+Left"
+            4 = "super+Left"
+
+            [firefox.swipe.left]
+            3 = "ctrl+Page_Up"
+
+            [device."Apple Magic Trackpad".swipe.left]
+            3 = "ctrl+Left"
+            4 = ""
+        "#, config::FileFormat::Toml)).unwrap();
+
+        let actions = GestureActions::new_with_config(config);
+        let configs = ["swipe.left.3".to_owned()];
+        let trackpad = Some("Apple Magic Trackpad");
+
+        assert_eq!(actions.get_first_for(&configs, Some("firefox"), trackpad), Some("ctrl+Page_Up".to_owned()));
+        assert_eq!(actions.get_first_for(&configs, Some("gedit"), trackpad), Some("ctrl+Left".to_owned()));
+        assert_eq!(actions.get_first_for(&configs, Some("gedit"), None), Some("alt+Left".to_owned()));
+        assert_eq!(actions.get_first_for(&["swipe.left.4".to_owned()], Some("gedit"), trackpad), None);
+    }
+
+    #[test]
+    fn missing_config() {
+        let missing = GestureActions::new(Some("/nonexistent/gesticle/config.toml"));
+        assert!(matches!(missing, Err(Error::ConfigNotFound(_))));
+    }
+}
@@ -3,28 +3,34 @@ use std::sync::{Mutex, Arc};
 use std::time::Duration;
 
 use dbus::blocking::Connection;
-use dbus_crossroads::Crossroads;
+use dbus_crossroads::{Crossroads, MethodErr};
 
 use crate::configuration::GestureActions;
 use dbus::Error;
 
 /// Creates a dbus-server in a new thread to allow reloading configuration when called
-pub fn server(actions_ref: Arc<Mutex<GestureActions>>) {
+pub fn server(actions_ref: Arc<Mutex<GestureActions>>) -> crate::Result<()> {
+    let c = Connection::new_session()?;
+    c.request_name("io.github.pguedes.gesticle", false, true, false)?;
     thread::spawn(move || {
-        let c = Connection::new_session().expect("d-bus session");
-        c.request_name("io.github.pguedes.gesticle", false, true, false).expect("d-bus name");
         let mut cr = Crossroads::new();
         let token = cr.register("io.github.pguedes.gesticle", move |b| {
             b.method("reload", (), (), move |_, _, _: ()| {
                 let mut actions = actions_ref.lock().unwrap();
-                actions.reload();
+                actions.reload().map_err(|e| {
+                    error!("failed to reload actions: {}", e);
+                    MethodErr::failed(&e)
+                })?;
                 debug!("actions reloaded: {:?}", actions);
                 Ok(())
             });
         });
         cr.insert("/actions/reload", &[token], ());
-        cr.serve(&c).expect("d-bus serve");
+        if let Err(e) = cr.serve(&c) {
+            error!("d-bus server stopped: {}", crate::Error::DBus(e));
+        }
     });
+    Ok(())
 }
 
 /// Call d-bus endpoint to request a configuration update
@@ -0,0 +1,201 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use input::{Device, DeviceCapability};
+use input::event::{DeviceEvent, EventTrait};
+
+use crate::error::{Error, Result};
+use crate::events::{open_libinput, InputSettings};
+
+/// A way of picking out input devices in `input.devices.include` and `input.devices.exclude`
+#[derive(Debug, Clone, PartialEq)]
+pub enum DeviceMatch {
+    /// the name of the device, ignoring case (ie: `Wacom Intuos S Pen`)
+    Name(String),
+    /// the vendor and product id in hex, as `lsusb` shows them (ie: `056a:0374`)
+    Id(u32, u32),
+    /// a udev property of the device, with the value it must have if any (ie: `udev:ID_INPUT_TOUCHSCREEN=1`)
+    Property(String, Option<String>),
+}
+
+impl DeviceMatch {
+    pub fn parse(s: &str) -> DeviceMatch {
+        if let Some(property) = s.strip_prefix("udev:") {
+            return match property.split_once('=') {
+                Some((name, value)) => DeviceMatch::Property(name.to_owned(), Some(value.to_owned())),
+                None => DeviceMatch::Property(property.to_owned(), None),
+            };
+        }
+        match parse_id(s) {
+            Some((vendor, product)) => DeviceMatch::Id(vendor, product),
+            None => DeviceMatch::Name(s.to_owned()),
+        }
+    }
+
+    pub fn matches(&self, device: &DeviceInfo) -> bool {
+        match self {
+            DeviceMatch::Name(name) => name.eq_ignore_ascii_case(&device.name),
+            DeviceMatch::Id(vendor, product) => *vendor == device.vendor && *product == device.product,
+            DeviceMatch::Property(name, value) => match (device.properties.get(name), value) {
+                (Some(actual), Some(value)) => actual == value,
+                (Some(_), None) => true,
+                (None, _) => false,
+            },
+        }
+    }
+}
+
+/// `vendor:product` as 4 hex digits each
+fn parse_id(s: &str) -> Option<(u32, u32)> {
+    let (vendor, product) = s.split_once(':')?;
+    if vendor.len() != 4 || product.len() != 4 {
+        return None;
+    }
+    Some((u32::from_str_radix(vendor, 16).ok()?, u32::from_str_radix(product, 16).ok()?))
+}
+
+/// What gesticle knows of an input device
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeviceInfo {
+    pub name: String,
+    /// the kernel name of the device (ie: `event5`)
+    pub sysname: String,
+    pub vendor: u32,
+    pub product: u32,
+    /// whether libinput recognizes gestures made on the device
+    pub gestures: bool,
+    /// the udev properties of the device (ie: `ID_INPUT_TOUCHPAD`)
+    pub properties: HashMap<String, String>,
+}
+
+impl DeviceInfo {
+    pub fn from_libinput(device: &Device) -> DeviceInfo {
+        // libinput is always created from udev so its devices belong to the same udev context
+        let properties = unsafe { device.udev_device() }
+            .map(|udev| udev.properties()
+                .map(|p| (p.name().to_string_lossy().into_owned(), p.value().to_string_lossy().into_owned()))
+                .collect())
+            .unwrap_or_default();
+        DeviceInfo {
+            name: device.name().to_owned(),
+            sysname: device.sysname().to_owned(),
+            vendor: device.id_vendor(),
+            product: device.id_product(),
+            gestures: device.has_capability(DeviceCapability::Gesture),
+            properties,
+        }
+    }
+}
+
+impl fmt::Display for DeviceInfo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {} ({:04x}:{:04x}), {}", self.sysname, self.name, self.vendor, self.product,
+               if self.gestures { "supports gestures" } else { "no gestures" })
+    }
+}
+
+/// Logs the devices libinput adds and removes and drops the events of the ones the `input` settings
+/// leave out
+pub(crate) struct DeviceFilter {
+    settings: InputSettings,
+    /// sysnames of the devices left out
+    ignored: HashSet<String>,
+}
+
+impl DeviceFilter {
+    pub fn new(settings: InputSettings) -> DeviceFilter {
+        DeviceFilter {
+            settings,
+            ignored: HashSet::new(),
+        }
+    }
+
+    /// whether `event` comes from a device that is not left out
+    pub fn allow(&mut self, event: &input::Event) -> bool {
+        match event {
+            input::Event::Device(DeviceEvent::Added(e)) => {
+                let device = DeviceInfo::from_libinput(&e.device());
+                if self.settings.allows(&device) {
+                    info!("device added: {}", device);
+                    true
+                } else {
+                    info!("device added: {}, ignored", device);
+                    self.ignored.insert(device.sysname);
+                    false
+                }
+            }
+            input::Event::Device(DeviceEvent::Removed(e)) => {
+                let device = DeviceInfo::from_libinput(&e.device());
+                info!("device removed: {}", device);
+                !self.ignored.remove(&device.sysname)
+            }
+            _ => self.ignored.is_empty() || !self.ignored.contains(event.device().sysname()),
+        }
+    }
+}
+
+/// The devices libinput sees on the seat of `settings`, including the ones they leave out
+pub fn list_devices(settings: &InputSettings) -> Result<Vec<DeviceInfo>> {
+    let mut libinput = open_libinput(settings)?;
+    libinput.dispatch().map_err(|e| Error::Io("failed to dispatch libinput events".to_owned(), e))?;
+    Ok(libinput
+        .filter_map(|event| match event {
+            input::Event::Device(DeviceEvent::Added(e)) => Some(DeviceInfo::from_libinput(&e.device())),
+            _ => None,
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use crate::configuration::GestureActions;
+    use crate::devices::{DeviceInfo, DeviceMatch};
+    use crate::events::InputSettings;
+
+    fn device(name: &str, vendor: u32, product: u32, properties: &[(&str, &str)]) -> DeviceInfo {
+        DeviceInfo {
+            name: name.to_owned(),
+            sysname: "event5".to_owned(),
+            vendor,
+            product,
+            gestures: false,
+            properties: properties.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect::<HashMap<_, _>>(),
+        }
+    }
+
+    #[test]
+    fn parse() {
+        assert_eq!(DeviceMatch::parse("Wacom Intuos S Pen"), DeviceMatch::Name("Wacom Intuos S Pen".to_owned()));
+        assert_eq!(DeviceMatch::parse("056a:0374"), DeviceMatch::Id(0x056a, 0x0374));
+        assert_eq!(DeviceMatch::parse("udev:ID_INPUT_TABLET=1"),
+                   DeviceMatch::Property("ID_INPUT_TABLET".to_owned(), Some("1".to_owned())));
+        assert_eq!(DeviceMatch::parse("udev:ID_INPUT_TABLET"), DeviceMatch::Property("ID_INPUT_TABLET".to_owned(), None));
+        assert_eq!(DeviceMatch::parse("SynPS/2 Synaptics TouchPad"),
+                   DeviceMatch::Name("SynPS/2 Synaptics TouchPad".to_owned()));
+    }
+
+    #[test]
+    fn include_exclude() {
+        let touchpad = device("SynPS/2 Synaptics TouchPad", 0x0002, 0x0007, &[("ID_INPUT_TOUCHPAD", "1")]);
+        let tablet = device("Wacom Intuos S Pen", 0x056a, 0x0374, &[("ID_INPUT_TABLET", "1")]);
+        let touchscreen = device("ELAN Touchscreen", 0x04f3, 0x2494, &[("ID_INPUT_TOUCHSCREEN", "1")]);
+
+        let mut config = config::Config::new();
+        config.set("input.devices.exclude", vec!["udev:ID_INPUT_TABLET=1", "04f3:2494"]).unwrap();
+        let input = InputSettings::from_config(&GestureActions::new_with_config(config.clone()));
+        assert!(input.allows(&touchpad));
+        assert!(!input.allows(&tablet));
+        assert!(!input.allows(&touchscreen));
+
+        config.set("input.devices.include", vec!["synps/2 synaptics touchpad", "ELAN Touchscreen"]).unwrap();
+        let input = InputSettings::from_config(&GestureActions::new_with_config(config));
+        assert!(input.allows(&touchpad));
+        assert!(!input.allows(&tablet));
+        // excluding takes precedence
+        assert!(!input.allows(&touchscreen));
+
+        assert!(InputSettings::default().allows(&tablet));
+    }
+}
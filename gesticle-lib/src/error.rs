@@ -0,0 +1,88 @@
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+/// Everything that can go wrong in gesticle
+#[derive(Debug)]
+pub enum Error {
+    /// the home directory of the current user could not be found
+    NoHome,
+    /// no configuration file was found in any of the places it is looked for
+    ConfigNotFound(String),
+    /// the configuration file could not be read or parsed
+    Config(config::ConfigError),
+    /// an io operation failed, with what was being done at the time
+    Io(String, io::Error),
+    /// a logger was already initialized
+    Logging(log::SetLoggerError),
+    /// libinput could not be assigned to a seat (ie: `seat0`)
+    Seat(String),
+    /// none of the input devices could be opened as the user is not allowed to
+    Permission(PathBuf),
+    /// reading input events failed
+    Input(String),
+    /// the d-bus session bus could not be used
+    DBus(dbus::Error),
+    /// a trace of gesture events is not valid
+    Trace(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::NoHome =>
+                write!(f, "cannot find the home directory of the current user: is $HOME set?"),
+            Error::ConfigNotFound(e) =>
+                write!(f, "no configuration file found, {}: reinstall gesticle or pass one with --config", e),
+            Error::Config(e) =>
+                write!(f, "invalid configuration: {}", e),
+            Error::Io(context, e) =>
+                write!(f, "{}: {}", context, e),
+            Error::Logging(e) =>
+                write!(f, "failed to initialize logging: {}", e),
+            Error::Seat(seat) =>
+                write!(f, "failed to assign libinput to {}: is udev running?", seat),
+            Error::Permission(path) =>
+                write!(f, "no permission to open {}: are you in the input group?", path.display()),
+            Error::Input(e) =>
+                write!(f, "failed to read input events: {}", e),
+            Error::DBus(e) =>
+                write!(f, "failed to use the d-bus session bus: {}: is a session bus running?",
+                       e.message().unwrap_or("unknown error")),
+            Error::Trace(e) =>
+                write!(f, "invalid trace: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Config(e) => Some(e),
+            Error::Io(_, e) => Some(e),
+            Error::Logging(e) => Some(e),
+            Error::DBus(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<config::ConfigError> for Error {
+    fn from(e: config::ConfigError) -> Error {
+        Error::Config(e)
+    }
+}
+
+impl From<log::SetLoggerError> for Error {
+    fn from(e: log::SetLoggerError) -> Error {
+        Error::Logging(e)
+    }
+}
+
+impl From<dbus::Error> for Error {
+    fn from(e: dbus::Error) -> Error {
+        Error::DBus(e)
+    }
+}
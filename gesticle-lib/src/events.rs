@@ -1,28 +1,333 @@
-use std::os::unix::io::RawFd;
-use std::path::Path;
-use std::thread::sleep;
-use std::time::Duration;
+use std::cell::RefCell;
+use std::iter::once;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd, RawFd};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
+use input::Event::{Gesture, Keyboard, Pointer, Touch};
+use input::event::EventTrait;
+use input::event::{GestureEvent, KeyboardEvent, PointerEvent, SwitchEvent, TabletPadEvent, TouchEvent};
+use input::event::gesture::{GestureEndEvent, GestureEventCoordinates, GestureEventTrait, GestureHoldEvent,
+                            GesturePinchEvent, GesturePinchEventTrait, GestureSwipeEvent};
+use input::event::keyboard::{KeyState, KeyboardEventTrait};
+use input::event::pointer::{ButtonState, PointerEventTrait};
+use input::event::switch::{SwitchEventTrait, SwitchState};
+use input::event::tablet_pad::TabletPadEventTrait;
+use input::event::touch::{TouchEventPosition, TouchEventSlot, TouchEventTrait};
 use input::Libinput;
 use input::LibinputInterface;
+use nix::Errno;
 use nix::fcntl::{OFlag, open};
+use nix::poll::{POLLIN, PollFd, poll};
 use nix::sys::stat::Mode;
-use nix::unistd::close;
 
-struct LibInputFile;
+use crate::configuration::GestureActions;
+use crate::devices::{DeviceFilter, DeviceInfo, DeviceMatch};
+use crate::error::{Error, Result};
+use crate::gestures::{Modifier, Switch};
+
+/// Settings for where input events are read from, from the `input` configuration
+#[derive(Debug, Clone)]
+pub struct InputSettings {
+    /// the seat whose devices are read (ie: `seat0`), on multi-seat machines each seat has its own devices
+    pub seat: String,
+    /// when there are any, only devices matching one of these are read
+    pub include: Vec<DeviceMatch>,
+    /// devices matching one of these are not read, even when included
+    pub exclude: Vec<DeviceMatch>,
+}
+
+impl InputSettings {
+    pub fn from_config(actions: &GestureActions) -> InputSettings {
+        let matches = |key: &str| actions.get_strings(key).iter().map(|s| DeviceMatch::parse(s)).collect();
+        InputSettings {
+            seat: actions.get("input.seat").unwrap_or_else(|| InputSettings::default().seat),
+            include: matches("input.devices.include"),
+            exclude: matches("input.devices.exclude"),
+        }
+    }
+
+    /// whether the events of `device` are read
+    pub fn allows(&self, device: &DeviceInfo) -> bool {
+        (self.include.is_empty() || self.include.iter().any(|m| m.matches(device)))
+            && !self.exclude.iter().any(|m| m.matches(device))
+    }
+}
+
+impl Default for InputSettings {
+    fn default() -> InputSettings {
+        InputSettings {
+            seat: "seat0".to_owned(),
+            include: vec![],
+            exclude: vec![],
+        }
+    }
+}
+
+/// Which devices libinput could open
+#[derive(Default)]
+struct DeviceAccess {
+    opened: usize,
+    denied: Vec<PathBuf>,
+}
+
+struct LibInputFile {
+    access: Rc<RefCell<DeviceAccess>>,
+}
 
 impl LibinputInterface for LibInputFile {
 
-    fn open_restricted(&mut self, path: &Path, flags: i32) -> Result<RawFd, i32> {
-        if let Ok(fd) = open(path, OFlag::from_bits_truncate(flags), Mode::empty()) {
-            Ok(fd)
-        } else {
-            Err(1)
+    fn open_restricted(&mut self, path: &Path, flags: i32) -> std::result::Result<OwnedFd, i32> {
+        match open(path, OFlag::from_bits_truncate(flags), Mode::empty()) {
+            Ok(fd) => {
+                self.access.borrow_mut().opened += 1;
+                // the fd was just opened so nothing else owns it
+                Ok(unsafe { OwnedFd::from_raw_fd(fd) })
+            }
+            Err(nix::Error::Sys(errno)) => {
+                if errno == Errno::EACCES || errno == Errno::EPERM {
+                    warn!("no permission to open {}", path.display());
+                    self.access.borrow_mut().denied.push(path.to_owned());
+                }
+                Err(errno as i32)
+            }
+            Err(e) => {
+                error!("failed to open {}: {:?}", path.display(), e);
+                Err(Errno::EINVAL as i32)
+            }
         }
     }
 
-    fn close_restricted(&mut self, fd: RawFd) {
-        let _ = close(fd);
+    fn close_restricted(&mut self, fd: OwnedFd) {
+        drop(fd);
+    }
+}
+
+/// A gesture event, independent of the backend (ie: libinput) that produced it.
+/// `time` is in microseconds and `device` is the name of the device the gesture was made on.
+///
+/// Touchscreens do not produce gestures, only touch points that come down, move and go up again in their
+/// `slot`. Their positions are in millimetres from the top left corner of the screen, which is `width` by
+/// `height` millimetres (0 when unknown).
+///
+/// Mice do not produce gestures either, strokes are drawn from their motion while a button (an evdev code,
+/// ie: `BTN_RIGHT`) is held.
+///
+/// Only the modifier keys of keyboards are read, to qualify gestures made while they are held, so that
+/// nothing typed ends up in a trace.
+///
+/// Switches are `on` when the lid is closed or the device is in tablet mode.
+///
+/// Tablet pads number their buttons, rings and strips from 0. Ring positions are in degrees clockwise
+/// from the top of the ring and strip positions go from 0 at the top to 1 at the bottom, both are -1 when the
+/// finger is lifted
+#[derive(Debug, Clone, PartialEq)]
+pub enum RawGestureEvent {
+    SwipeBegin { fingers: i32, time: u64, device: String },
+    SwipeUpdate { dx: f64, dy: f64, time: u64, device: String },
+    SwipeEnd { cancelled: bool, time: u64, device: String },
+    PinchBegin { fingers: i32, scale: f64, time: u64, device: String },
+    /// `angle` is the rotation since the previous update in degrees, `scale` is relative to the begin of the pinch
+    PinchUpdate { dx: f64, dy: f64, scale: f64, angle: f64, time: u64, device: String },
+    PinchEnd { cancelled: bool, scale: f64, time: u64, device: String },
+    HoldBegin { fingers: i32, time: u64, device: String },
+    HoldEnd { cancelled: bool, time: u64, device: String },
+    TouchDown { slot: u32, x: f64, y: f64, width: f64, height: f64, time: u64, device: String },
+    TouchMotion { slot: u32, x: f64, y: f64, time: u64, device: String },
+    TouchUp { slot: u32, time: u64, device: String },
+    TouchCancel { slot: u32, time: u64, device: String },
+    PointerButton { button: u32, pressed: bool, time: u64, device: String },
+    PointerMotion { dx: f64, dy: f64, time: u64, device: String },
+    Key { key: u32, pressed: bool, time: u64, device: String },
+    Switch { switch: Switch, on: bool, time: u64, device: String },
+    PadButton { button: u32, pressed: bool, time: u64, device: String },
+    PadRing { ring: u32, position: f64, time: u64, device: String },
+    PadStrip { strip: u32, position: f64, time: u64, device: String },
+}
+
+impl RawGestureEvent {
+
+    /// the gesture event for a libinput event, if it is one
+    pub fn from_libinput(event: input::Event) -> Option<RawGestureEvent> {
+        let event = match event {
+            Gesture(event) => event,
+            Touch(event) => return RawGestureEvent::from_touch(event),
+            Pointer(event) => return RawGestureEvent::from_pointer(event),
+            Keyboard(event) => return RawGestureEvent::from_keyboard(event),
+            input::Event::Switch(event) => return RawGestureEvent::from_switch(event),
+            input::Event::TabletPad(event) => return RawGestureEvent::from_tablet_pad(event),
+            _ => return None,
+        };
+        let time = event.time_usec();
+        let device = event.device().name().to_owned();
+
+        match event {
+            GestureEvent::Swipe(GestureSwipeEvent::Begin(e)) =>
+                Some(RawGestureEvent::SwipeBegin { fingers: e.finger_count(), time, device }),
+            GestureEvent::Swipe(GestureSwipeEvent::Update(e)) =>
+                Some(RawGestureEvent::SwipeUpdate { dx: e.dx(), dy: e.dy(), time, device }),
+            GestureEvent::Swipe(GestureSwipeEvent::End(e)) =>
+                Some(RawGestureEvent::SwipeEnd { cancelled: e.cancelled(), time, device }),
+            GestureEvent::Pinch(GesturePinchEvent::Begin(e)) =>
+                Some(RawGestureEvent::PinchBegin { fingers: e.finger_count(), scale: e.scale(), time, device }),
+            GestureEvent::Pinch(GesturePinchEvent::Update(e)) =>
+                Some(RawGestureEvent::PinchUpdate {
+                    dx: e.dx(),
+                    dy: e.dy(),
+                    scale: e.scale(),
+                    angle: e.angle_delta(),
+                    time,
+                    device,
+                }),
+            GestureEvent::Pinch(GesturePinchEvent::End(e)) =>
+                Some(RawGestureEvent::PinchEnd { cancelled: e.cancelled(), scale: e.scale(), time, device }),
+            GestureEvent::Hold(GestureHoldEvent::Begin(e)) =>
+                Some(RawGestureEvent::HoldBegin { fingers: e.finger_count(), time, device }),
+            GestureEvent::Hold(GestureHoldEvent::End(e)) =>
+                Some(RawGestureEvent::HoldEnd { cancelled: e.cancelled(), time, device }),
+            _ => None,
+        }
+    }
+
+    fn from_touch(event: TouchEvent) -> Option<RawGestureEvent> {
+        let time = event.time_usec();
+        let device = event.device().name().to_owned();
+
+        match event {
+            TouchEvent::Down(e) => {
+                let (width, height) = e.device().size().unwrap_or((0.0, 0.0));
+                Some(RawGestureEvent::TouchDown { slot: e.seat_slot(), x: e.x(), y: e.y(), width, height, time, device })
+            }
+            TouchEvent::Motion(e) =>
+                Some(RawGestureEvent::TouchMotion { slot: e.seat_slot(), x: e.x(), y: e.y(), time, device }),
+            TouchEvent::Up(e) =>
+                Some(RawGestureEvent::TouchUp { slot: e.seat_slot(), time, device }),
+            TouchEvent::Cancel(e) =>
+                Some(RawGestureEvent::TouchCancel { slot: e.seat_slot(), time, device }),
+            _ => None,
+        }
+    }
+
+    fn from_pointer(event: PointerEvent) -> Option<RawGestureEvent> {
+        match event {
+            PointerEvent::Button(e) => Some(RawGestureEvent::PointerButton {
+                button: e.button(),
+                pressed: e.button_state() == ButtonState::Pressed,
+                time: e.time_usec(),
+                device: e.device().name().to_owned(),
+            }),
+            PointerEvent::Motion(e) => Some(RawGestureEvent::PointerMotion {
+                dx: e.dx(),
+                dy: e.dy(),
+                time: e.time_usec(),
+                device: e.device().name().to_owned(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn from_keyboard(event: KeyboardEvent) -> Option<RawGestureEvent> {
+        match event {
+            KeyboardEvent::Key(e) if Modifier::of_key(e.key()).is_some() => Some(RawGestureEvent::Key {
+                key: e.key(),
+                pressed: e.key_state() == KeyState::Pressed,
+                time: e.time_usec(),
+                device: e.device().name().to_owned(),
+            }),
+            _ => None,
+        }
+    }
+
+    fn from_switch(event: SwitchEvent) -> Option<RawGestureEvent> {
+        let event = match event {
+            SwitchEvent::Toggle(e) => e,
+            _ => return None,
+        };
+        let switch = match event.switch()? {
+            input::event::switch::Switch::Lid => Switch::Lid,
+            input::event::switch::Switch::TabletMode => Switch::Tablet,
+            _ => return None,
+        };
+        Some(RawGestureEvent::Switch {
+            switch,
+            on: event.switch_state() == SwitchState::On,
+            time: event.time_usec(),
+            device: event.device().name().to_owned(),
+        })
+    }
+
+    fn from_tablet_pad(event: TabletPadEvent) -> Option<RawGestureEvent> {
+        match event {
+            TabletPadEvent::Button(e) => Some(RawGestureEvent::PadButton {
+                button: e.button_number(),
+                pressed: e.button_state() == ButtonState::Pressed,
+                time: e.time_usec(),
+                device: e.device().name().to_owned(),
+            }),
+            TabletPadEvent::Ring(e) => Some(RawGestureEvent::PadRing {
+                ring: e.number(),
+                position: e.position(),
+                time: e.time_usec(),
+                device: e.device().name().to_owned(),
+            }),
+            TabletPadEvent::Strip(e) => Some(RawGestureEvent::PadStrip {
+                strip: e.number(),
+                position: e.position(),
+                time: e.time_usec(),
+                device: e.device().name().to_owned(),
+            }),
+            _ => None,
+        }
+    }
+
+    /// when this event happened in microseconds
+    pub fn time(&self) -> u64 {
+        match self {
+            RawGestureEvent::SwipeBegin { time, .. } |
+            RawGestureEvent::SwipeUpdate { time, .. } |
+            RawGestureEvent::SwipeEnd { time, .. } |
+            RawGestureEvent::PinchBegin { time, .. } |
+            RawGestureEvent::PinchUpdate { time, .. } |
+            RawGestureEvent::PinchEnd { time, .. } |
+            RawGestureEvent::HoldBegin { time, .. } |
+            RawGestureEvent::HoldEnd { time, .. } |
+            RawGestureEvent::TouchDown { time, .. } |
+            RawGestureEvent::TouchMotion { time, .. } |
+            RawGestureEvent::TouchUp { time, .. } |
+            RawGestureEvent::TouchCancel { time, .. } |
+            RawGestureEvent::PointerButton { time, .. } |
+            RawGestureEvent::PointerMotion { time, .. } |
+            RawGestureEvent::Key { time, .. } |
+            RawGestureEvent::Switch { time, .. } |
+            RawGestureEvent::PadButton { time, .. } |
+            RawGestureEvent::PadRing { time, .. } |
+            RawGestureEvent::PadStrip { time, .. } => *time,
+        }
+    }
+
+    /// the name of the device this event comes from
+    pub fn device(&self) -> &str {
+        match self {
+            RawGestureEvent::SwipeBegin { device, .. } |
+            RawGestureEvent::SwipeUpdate { device, .. } |
+            RawGestureEvent::SwipeEnd { device, .. } |
+            RawGestureEvent::PinchBegin { device, .. } |
+            RawGestureEvent::PinchUpdate { device, .. } |
+            RawGestureEvent::PinchEnd { device, .. } |
+            RawGestureEvent::HoldBegin { device, .. } |
+            RawGestureEvent::HoldEnd { device, .. } |
+            RawGestureEvent::TouchDown { device, .. } |
+            RawGestureEvent::TouchMotion { device, .. } |
+            RawGestureEvent::TouchUp { device, .. } |
+            RawGestureEvent::TouchCancel { device, .. } |
+            RawGestureEvent::PointerButton { device, .. } |
+            RawGestureEvent::PointerMotion { device, .. } |
+            RawGestureEvent::Key { device, .. } |
+            RawGestureEvent::Switch { device, .. } |
+            RawGestureEvent::PadButton { device, .. } |
+            RawGestureEvent::PadRing { device, .. } |
+            RawGestureEvent::PadStrip { device, .. } => device,
+        }
     }
 }
 
@@ -38,18 +343,128 @@ impl<F> EventSink for F where F: FnMut(input::Event) {
     }
 }
 
+/// A libinput context for the devices of the configured seat, failing when none of them could be opened
+/// for lack of permissions
+pub(crate) fn open_libinput(settings: &InputSettings) -> Result<Libinput> {
+    let access = Rc::new(RefCell::new(DeviceAccess::default()));
+    let mut libinput = Libinput::new_with_udev(LibInputFile { access: access.clone() });
+    libinput.udev_assign_seat(&settings.seat).map_err(|_| Error::Seat(settings.seat.to_owned()))?;
+
+    let access = access.borrow();
+    match access.denied.first() {
+        Some(denied) if access.opened == 0 => Err(Error::Permission(denied.to_owned())),
+        _ => Ok(libinput),
+    }
+}
+
 /// Process all incoming libinput events in an infinite loop
-pub fn input_events<S>(sink: &mut S) where S: EventSink {
+pub fn input_events<S>(settings: &InputSettings, sink: &mut S) -> Result<()> where S: EventSink {
+    input_events_until(settings, sink, &[]).map(|_| ())
+}
+
+/// Process all incoming libinput events until one of `wakers` (ie: an eventfd or a signalfd) becomes
+/// readable, blocking while there are none. Returns the file descriptor that stopped processing
+pub fn input_events_until<S>(settings: &InputSettings, sink: &mut S, wakers: &[RawFd]) -> Result<RawFd>
+    where S: EventSink {
+
+    let mut libinput = open_libinput(settings)?;
+    dispatch_until(&mut libinput, settings, sink, wakers)
+}
 
-    let mut libinput = Libinput::new_with_udev(LibInputFile);
+/// Process the events of an open libinput context like `input_events_until`, leaving out the devices
+/// `settings` do not allow
+pub(crate) fn dispatch_until<S>(libinput: &mut Libinput, settings: &InputSettings, sink: &mut S, wakers: &[RawFd])
+    -> Result<RawFd> where S: EventSink {
 
-    libinput.udev_assign_seat("seat0").unwrap();
-    
+    let mut devices = DeviceFilter::new(settings.clone());
     loop {
-        libinput.dispatch().unwrap();
+        libinput.dispatch().map_err(|e| Error::Io("failed to dispatch libinput events".to_owned(), e))?;
         while let Some(event) = libinput.next() {
-            sink.event(event);
+            if devices.allow(&event) {
+                sink.event(event);
+            }
         }
-        sleep(Duration::from_millis(10));
+
+        if let Some(waker) = wait(libinput.as_raw_fd(), wakers)? {
+            return Ok(waker);
+        }
+    }
+}
+
+/// Block until there are libinput events to read from `input` or one of `wakers` becomes readable, returning
+/// the waker if it was one. Interrupted waits are resumed
+pub(crate) fn wait(input: RawFd, wakers: &[RawFd]) -> Result<Option<RawFd>> {
+    let mut fds: Vec<PollFd> = once(input).chain(wakers.iter().cloned())
+        .map(|fd| PollFd::new(fd, POLLIN))
+        .collect();
+    loop {
+        match poll(&mut fds, -1) {
+            Ok(_) => break,
+            Err(nix::Error::Sys(Errno::EINTR)) => continue,
+            Err(e) => return Err(Error::Input(format!("{:?}", e))),
+        }
+    }
+
+    let woken = fds[1..].iter().position(|fd| fd.revents().is_some_and(|r| r.contains(POLLIN)));
+    Ok(woken.map(|i| wakers[i]))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::mpsc;
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    use nix::sys::eventfd::{EFD_CLOEXEC, eventfd};
+    use nix::sys::signal::{SigAction, SigHandler, SaFlags, SigSet, Signal, sigaction};
+    use nix::unistd::{pipe, write};
+
+    use crate::configuration::GestureActions;
+    use crate::events::{InputSettings, wait};
+
+    #[test]
+    fn seat() {
+        let mut config = config::Config::new();
+        assert_eq!(InputSettings::from_config(&GestureActions::new_with_config(config.clone())).seat, "seat0");
+
+        config.set("input.seat", "seat1").unwrap();
+        let actions = GestureActions::new_with_config(config);
+        assert_eq!(InputSettings::from_config(&actions).seat, "seat1");
+        assert!(!actions.apps().unwrap().contains(&"input".to_owned()));
+    }
+
+    extern "C" fn ignore(_: libc::c_int) {}
+
+    #[test]
+    fn wake() {
+        // libinput events that never come, and an eventfd signalled once the wait was interrupted by a signal
+        let (input, _) = pipe().unwrap();
+        let waker = eventfd(0, EFD_CLOEXEC).unwrap();
+        let handler = SigAction::new(SigHandler::Handler(ignore), SaFlags::empty(), SigSet::empty());
+        unsafe { sigaction(Signal::SIGUSR1, &handler).unwrap() };
+
+        let (tx, rx) = mpsc::channel();
+        let waiting = thread::spawn(move || {
+            tx.send(unsafe { libc::pthread_self() }).unwrap();
+            wait(input, &[waker]).unwrap()
+        });
+        let thread = rx.recv().unwrap();
+        thread::sleep(Duration::from_millis(50));
+        unsafe { libc::pthread_kill(thread, libc::SIGUSR1) };
+        thread::sleep(Duration::from_millis(50));
+
+        let signalled = Instant::now();
+        write(waker, &1u64.to_ne_bytes()).unwrap();
+        assert_eq!(waiting.join().unwrap(), Some(waker));
+        assert!(signalled.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn wait_for_input() {
+        let (input, events) = pipe().unwrap();
+        let waker = eventfd(0, EFD_CLOEXEC).unwrap();
+        write(events, &[1]).unwrap();
+        // returns as soon as there are events, without the waker being signalled
+        assert_eq!(wait(input, &[waker]).unwrap(), None);
     }
 }
@@ -1,71 +1,274 @@
-use std::f64::consts::PI;
+use std::collections::{HashMap, HashSet};
 use std::fmt;
+use std::fs::File;
 use std::fmt::Formatter;
-use std::mem::swap;
-use std::sync::mpsc;
+use std::mem::{swap, take};
+use std::ops::Deref;
+use std::os::unix::io::{AsRawFd, FromRawFd, OwnedFd};
+use std::sync::{Arc, mpsc};
 use std::thread;
+use std::thread::JoinHandle;
 
-use input::event::gesture::{GesturePinchEndEvent, GesturePinchEvent, GesturePinchUpdateEvent};
-use input::Event::Gesture;
-use input::event::gesture::GestureEndEvent;
-use input::event::gesture::GestureEventCoordinates;
-use input::event::gesture::GestureEventTrait;
-use input::event::gesture::GesturePinchEventTrait;
-use input::event::gesture::GestureSwipeEndEvent;
-use input::event::gesture::GestureSwipeEvent::Begin;
-use input::event::gesture::GestureSwipeEvent::End;
-use input::event::gesture::GestureSwipeEvent::Update;
-use input::event::gesture::GestureSwipeUpdateEvent;
-use input::event::GestureEvent::*;
-
-use crate::events::input_events;
+use nix::sys::eventfd::{EFD_CLOEXEC, eventfd};
+use nix::unistd::write;
+
+use crate::configuration::GestureActions;
+use crate::error;
+use crate::error::Error;
+use crate::events::{dispatch_until, open_libinput, InputSettings, RawGestureEvent};
+use crate::trace::TraceWriter;
+
+/// Settings that control when gestures are triggered, from the `gesture.trigger` configuration
+#[derive(Debug, Clone)]
+pub struct GestureTriggers {
+    pub pinch_in_scale: f64,
+    pub pinch_out_scale: f64,
+    pub rotation_angle: f64,
+    /// whether rotations trigger mid-gesture, again every `rotation_angle` degrees, instead of when they end
+    pub rotation_continuous: bool,
+    pub swipe_angle_tolerance: f64,
+    pub swipe_min_distance: f64,
+    pub swipe_fast_velocity: f64,
+    pub swipe_long_distance: f64,
+    /// how long (in milliseconds) fingers have to rest on the touchpad for a hold
+    pub hold_min_duration: f64,
+    /// how far (in millimetres) fingers have to move on a touchscreen for a swipe
+    pub touch_min_distance: f64,
+    /// how much the fingers on a touchscreen have to spread or close, relative to where they started, for a pinch
+    pub touch_pinch_scale: f64,
+    /// how close (in millimetres) to the edge of a touchscreen a finger has to come down for an edge swipe
+    pub touch_edge_size: f64,
+    /// the mouse button (an evdev code, ie: 273 for `BTN_RIGHT`) held down to draw a stroke, none by default as
+    /// the button still reaches the application under the pointer
+    pub mouse_button: Option<u32>,
+    /// how far (in pointer units) the mouse has to move in a direction for it to be part of a stroke
+    pub mouse_min_distance: f64,
+    /// how far (in degrees) a finger has to go around a tablet pad ring to trigger it, again for every step
+    pub pad_ring_step: f64,
+    /// how far (from 0 at the top to 1 at the bottom) a finger has to slide on a tablet pad strip to trigger it,
+    /// again for every step
+    pub pad_strip_step: f64,
+    /// distances after which swipes are triggered mid-gesture, keyed by the swipe configuration (ie: `swipe.left.3`),
+    /// swipes not in here only trigger when they end
+    pub continuous_swipe_steps: HashMap<String, f64>,
+    /// how long (in milliseconds) after triggering a gesture the same gesture is ignored
+    pub cooldown: f64,
+    /// cooldowns overriding the default one, keyed by the gesture configuration (ie: `swipe.left.3`)
+    pub cooldowns: HashMap<String, f64>,
+    /// triggers of the devices configured in a `[device."<name>".gesture.trigger]` table, keyed by their lowercased
+    /// name. The cooldowns of these are not used as the cooldown is the same for every device
+    pub devices: HashMap<String, GestureTriggers>,
+}
+
+impl GestureTriggers {
+    pub fn from_config(actions: &GestureActions) -> GestureTriggers {
+        let mut triggers = GestureTriggers::from_device_config(actions, None);
+        triggers.devices = actions.devices().into_iter()
+            .map(|device| {
+                let device_triggers = GestureTriggers::from_device_config(actions, Some(&device));
+                (device, device_triggers)
+            })
+            .collect();
+        triggers
+    }
+
+    /// the triggers of `device`, falling back to the global ones for anything it does not set
+    fn from_device_config(actions: &GestureActions, device: Option<&str>) -> GestureTriggers {
+        let float = |key: &str| actions.get_float_for_device(key, device);
+        GestureTriggers {
+            pinch_in_scale: float("gesture.trigger.pinch.in.scale").unwrap_or(0.0),
+            pinch_out_scale: float("gesture.trigger.pinch.out.scale").unwrap_or(0.0),
+            rotation_angle: float("gesture.trigger.rotation.angle").unwrap_or(50.0),
+            rotation_continuous: actions.get_bool_for_device("gesture.trigger.rotation.continuous", device)
+                .unwrap_or(false),
+            swipe_angle_tolerance: float("gesture.trigger.swipe.angle_tolerance").map(swipe_angle_tolerance)
+                .unwrap_or(15.0),
+            swipe_min_distance: float("gesture.trigger.swipe.min_distance").unwrap_or(0.0),
+            swipe_fast_velocity: float("gesture.trigger.swipe.fast_velocity").unwrap_or(0.0),
+            swipe_long_distance: float("gesture.trigger.swipe.long_distance").unwrap_or(0.0),
+            hold_min_duration: float("gesture.trigger.hold.min_duration").unwrap_or(500.0),
+            touch_min_distance: float("gesture.trigger.touch.min_distance").unwrap_or(20.0),
+            touch_pinch_scale: float("gesture.trigger.touch.pinch_scale").unwrap_or(0.2),
+            touch_edge_size: float("gesture.trigger.touch.edge_size").unwrap_or(5.0),
+            mouse_button: actions.get_for_device("gesture.trigger.mouse.button", device)
+                .and_then(|button| {
+                    let code = mouse_button(&button);
+                    if code.is_none() {
+                        warn!("unknown mouse button {}, mouse strokes are disabled", button);
+                    }
+                    code
+                }),
+            mouse_min_distance: float("gesture.trigger.mouse.min_distance").unwrap_or(50.0),
+            pad_ring_step: float("gesture.trigger.pad.ring_step").unwrap_or(15.0),
+            pad_strip_step: float("gesture.trigger.pad.strip_step").unwrap_or(0.1),
+            continuous_swipe_steps: actions.get_floats_for_device("gesture.trigger.continuous", device),
+            cooldown: actions.get_float("gesture.trigger.cooldown.default").unwrap_or(0.0),
+            cooldowns: actions.get_floats("gesture.trigger.cooldown").into_iter()
+                .filter(|(k, _)| k != "default")
+                .collect(),
+            devices: HashMap::new(),
+        }
+    }
+}
+
+const BTN_LEFT: u32 = 0x110;
+const BTN_RIGHT: u32 = 0x111;
+const BTN_MIDDLE: u32 = 0x112;
+const BTN_SIDE: u32 = 0x113;
+const BTN_EXTRA: u32 = 0x114;
+
+/// the swipe angle tolerance within 0 (only diagonals) and 45 (no diagonals) degrees, warning about the ones out of it
+pub fn swipe_angle_tolerance(tolerance: f64) -> f64 {
+    let clamped = tolerance.clamp(0.0, 45.0);
+    if clamped != tolerance {
+        warn!("swipe angle tolerance {} is not between 0 and 45 degrees, using {}", tolerance, clamped);
+    }
+    clamped
+}
+
+/// the evdev code of a mouse button given by name (ie: `middle`) or by code
+fn mouse_button(name: &str) -> Option<u32> {
+    match name.to_lowercase().as_str() {
+        "left" => Some(BTN_LEFT),
+        "right" => Some(BTN_RIGHT),
+        "middle" => Some(BTN_MIDDLE),
+        "side" => Some(BTN_SIDE),
+        "extra" => Some(BTN_EXTRA),
+        code => code.parse().ok(),
+    }
+}
+
+/// Keeps the same gesture from triggering again until its cooldown has passed, so that an accidental
+/// double swipe or a pinch that keeps on going does not fire twice
+struct Cooldown {
+    default: f64,
+    cooldowns: HashMap<String, f64>,
+    last_triggered: HashMap<String, u64>,
+}
+
+impl Cooldown {
+    fn new(default: f64, cooldowns: HashMap<String, f64>) -> Cooldown {
+        Cooldown {
+            default,
+            cooldowns,
+            last_triggered: HashMap::new(),
+        }
+    }
+
+    /// the cooldown in milliseconds for a gesture, from its most specific configuration
+    fn duration(&self, t: &GestureType) -> f64 {
+        t.to_configs().iter()
+            .find_map(|c| self.cooldowns.get(c))
+            .copied()
+            .unwrap_or(self.default)
+    }
+
+    /// whether gesture `t` triggering at `time` (in microseconds) is past its cooldown, and if so start it over
+    fn allow(&mut self, t: &GestureType, time: u64) -> bool {
+        let cooldown = self.duration(t);
+        if cooldown <= 0.0 {
+            return true;
+        }
+        let key = t.to_config();
+        if let Some(last) = self.last_triggered.get(&key) {
+            let elapsed = time.saturating_sub(*last) / 1000;
+            if (elapsed as f64) < cooldown {
+                debug!("suppressing {} triggered {}ms after the last one, cooldown is {}ms", key, elapsed, cooldown);
+                return false;
+            }
+        }
+        self.last_triggered.insert(key, time);
+        true
+    }
+}
 
 #[derive(Copy, Clone)]
 struct SwipeGesture {
     dx: f64,
     dy: f64,
     fingers: i32,
-    cancelled: bool,
+    angle_tolerance: f64,
+    fast_velocity: f64,
+    long_distance: f64,
+    start_time: u64,
+    time: u64,
+    peak_velocity: f64,
+    /// why this is not a gesture, once it was cancelled
+    cancelled: Option<&'static str>,
 }
 
 impl SwipeGesture {
-    fn new(fingers: i32) -> SwipeGesture {
+    fn new(fingers: i32, angle_tolerance: f64, fast_velocity: f64, long_distance: f64, time: u64) -> SwipeGesture {
         SwipeGesture {
             dx: 0.0,
             dy: 0.0,
-            cancelled: false,
+            cancelled: None,
             fingers,
+            angle_tolerance,
+            fast_velocity,
+            long_distance,
+            start_time: time,
+            time,
+            peak_velocity: 0.0,
         }
     }
 
-    fn add(&mut self, dx: f64, dy: f64) {
+    /// add movement that happened at `time` (in microseconds)
+    fn add(&mut self, dx: f64, dy: f64, time: u64) {
+        let elapsed = time.saturating_sub(self.time);
+        if elapsed > 0 {
+            let velocity = dx.hypot(dy) / (elapsed as f64 / 1000.0);
+            self.peak_velocity = self.peak_velocity.max(velocity);
+        }
         self.dx += dx;
         self.dy += dy;
+        self.time = time;
     }
 
-    fn cancel(&mut self) {
-        self.cancelled = true;
+    /// duration of this swipe so far in milliseconds
+    fn duration(&self) -> u64 {
+        self.time.saturating_sub(self.start_time) / 1000
+    }
+
+    fn speed(&self) -> Option<SwipeSpeed> {
+        if self.fast_velocity <= 0.0 {
+            None
+        } else if self.peak_velocity >= self.fast_velocity {
+            Some(SwipeSpeed::Fast)
+        } else {
+            Some(SwipeSpeed::Slow)
+        }
+    }
+
+    fn length(&self) -> Option<SwipeLength> {
+        if self.long_distance <= 0.0 {
+            None
+        } else if self.distance() >= self.long_distance {
+            Some(SwipeLength::Long)
+        } else {
+            Some(SwipeLength::Short)
+        }
+    }
+
+    fn cancel(&mut self, reason: &'static str) {
+        self.cancelled = Some(reason);
+    }
+
+    fn distance(&self) -> f64 {
+        self.dx.hypot(self.dy)
+    }
+
+    fn reset(&mut self) {
+        self.dx = 0.0;
+        self.dy = 0.0;
     }
 
     fn direction(&self) -> Option<SwipeDirection> {
-        let theta = (self.dy / self.dx).atan();
-        let t: f64 = 180.into();
-        let angle = (theta * (t / PI)).abs();
-        if 75.0 < angle && angle < 105.0 {
-            return if self.dy > 0.0 {
-                Some(SwipeDirection::Down)
-            } else {
-                Some(SwipeDirection::Up)
-            };
-        } else if 0.0 < angle && angle < 15.0 {
-            return if self.dx > 0.0 {
-                Some(SwipeDirection::Right)
-            } else {
-                Some(SwipeDirection::Left)
-            };
+        let direction = SwipeDirection::of_delta(self.dx, self.dy, self.angle_tolerance);
+        if direction.is_none() {
+            warn!("unknown direction: {:?}", self);
         }
-        warn!("unknown direction: {:?} direction = {:?}", self, angle);
-        return None;
+        direction
     }
 }
 
@@ -73,31 +276,36 @@ impl fmt::Debug for SwipeGesture {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "({}, {}) fingers = {} cancelled? {}",
-            self.dx, self.dy, self.fingers, self.cancelled
+            "({}, {}) fingers = {} duration = {}ms peak velocity = {} cancelled? {:?}",
+            self.dx, self.dy, self.fingers, self.duration(), self.peak_velocity, self.cancelled
         )
     }
 }
 
 #[derive(Copy, Clone)]
 struct PinchGesture {
+    fingers: i32,
     initial_scale: f64,
     scale: f64,
     dx: f64,
     dy: f64,
     angle: f64,
-    cancelled: bool,
+    rotation_angle: f64,
+    /// why this is not a gesture, once it was cancelled
+    cancelled: Option<&'static str>,
 }
 
 impl PinchGesture {
-    fn new(scale: f64) -> PinchGesture {
+    fn new(fingers: i32, scale: f64, rotation_angle: f64) -> PinchGesture {
         PinchGesture {
+            fingers,
             initial_scale: scale,
             scale: 0.0,
             dx: 0.0,
             dy: 0.0,
             angle: 0.0,
-            cancelled: false,
+            rotation_angle,
+            cancelled: None,
         }
     }
 
@@ -109,12 +317,12 @@ impl PinchGesture {
     }
 
     fn is_rotation(&self) -> bool {
-        self.angle > 50.0 || self.angle < -50.0
+        RotationDirection::of_angle(self.angle, self.rotation_angle).is_some()
     }
 
     fn rotation_direction(&self) -> Option<RotationDirection> {
         if self.is_rotation() {
-            return RotationDirection::of_angle(self.angle);
+            return RotationDirection::of_angle(self.angle, self.rotation_angle);
         }
         None
     }
@@ -126,8 +334,8 @@ impl PinchGesture {
         None
     }
 
-    fn cancel(&mut self) {
-        self.cancelled = true;
+    fn cancel(&mut self, reason: &'static str) {
+        self.cancelled = Some(reason);
     }
 }
 
@@ -135,55 +343,527 @@ impl fmt::Debug for PinchGesture {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "scale = {}, ({}, {}) angle = {} cancelled? {}",
-            self.scale, self.dx, self.dy, self.angle, self.cancelled
+            "scale = {}, ({}, {}) angle = {} fingers = {} cancelled? {:?}",
+            self.scale, self.dx, self.dy, self.angle, self.fingers, self.cancelled
+        )
+    }
+}
+
+#[derive(Copy, Clone)]
+struct HoldGesture {
+    fingers: i32,
+    start_time: u64,
+    time: u64,
+    /// why this is not a gesture, once it was cancelled
+    cancelled: Option<&'static str>,
+}
+
+impl HoldGesture {
+    fn new(fingers: i32, time: u64) -> HoldGesture {
+        HoldGesture {
+            fingers,
+            start_time: time,
+            time,
+            cancelled: None,
+        }
+    }
+
+    fn end(&mut self, time: u64) {
+        self.time = time;
+    }
+
+    /// duration of this hold in milliseconds
+    fn duration(&self) -> u64 {
+        self.time.saturating_sub(self.start_time) / 1000
+    }
+
+    fn cancel(&mut self, reason: &'static str) {
+        self.cancelled = Some(reason);
+    }
+}
+
+impl fmt::Debug for HoldGesture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "fingers = {} duration = {}ms cancelled? {:?}",
+            self.fingers, self.duration(), self.cancelled
+        )
+    }
+}
+
+/// Where a finger came down on a touchscreen and where it is now, or was lifted
+#[derive(Copy, Clone, Debug)]
+struct TouchPoint {
+    start: (f64, f64),
+    now: (f64, f64),
+}
+
+/// Fingers on a touchscreen, from when the first one comes down until the last one is lifted
+#[derive(Clone)]
+struct TouchGesture {
+    /// every touch point of the gesture, the lifted ones staying where they were lifted
+    points: Vec<TouchPoint>,
+    /// the slots still down and their touch point, a slot is reused by the next finger after it is lifted
+    down: HashMap<u32, usize>,
+    /// the most fingers down at the same time
+    fingers: i32,
+    /// the edge of the screen the first finger came down on
+    edge: Option<ScreenEdge>,
+    angle_tolerance: f64,
+    min_distance: f64,
+    pinch_scale: f64,
+    rotation_angle: f64,
+    /// why this is not a gesture, once it was cancelled
+    cancelled: Option<&'static str>,
+}
+
+impl TouchGesture {
+    fn new(edge: Option<ScreenEdge>, angle_tolerance: f64, min_distance: f64, pinch_scale: f64, rotation_angle: f64)
+        -> TouchGesture {
+        TouchGesture {
+            points: vec![],
+            down: HashMap::new(),
+            fingers: 0,
+            edge,
+            angle_tolerance,
+            min_distance,
+            pinch_scale,
+            rotation_angle,
+            cancelled: None,
+        }
+    }
+
+    fn down(&mut self, slot: u32, x: f64, y: f64) {
+        self.points.push(TouchPoint { start: (x, y), now: (x, y) });
+        self.down.insert(slot, self.points.len() - 1);
+        self.fingers = self.fingers.max(self.down.len() as i32);
+    }
+
+    fn motion(&mut self, slot: u32, x: f64, y: f64) {
+        let points = &mut self.points;
+        if let Some(point) = self.down.get(&slot).and_then(|i| points.get_mut(*i)) {
+            point.now = (x, y);
+        }
+    }
+
+    fn up(&mut self, slot: u32) {
+        self.down.remove(&slot);
+    }
+
+    fn cancel(&mut self, reason: &'static str) {
+        self.cancelled = Some(reason);
+    }
+
+    /// how far the fingers moved on average
+    fn translation(&self) -> (f64, f64) {
+        let n = self.points.len().max(1) as f64;
+        let (dx, dy) = self.points.iter()
+            .fold((0.0, 0.0), |(dx, dy), p| (dx + p.now.0 - p.start.0, dy + p.now.1 - p.start.1));
+        (dx / n, dy / n)
+    }
+
+    fn centroid(&self, current: bool) -> (f64, f64) {
+        let n = self.points.len().max(1) as f64;
+        let (x, y) = self.points.iter()
+            .map(|p| if current { p.now } else { p.start })
+            .fold((0.0, 0.0), |(cx, cy), (x, y)| (cx + x, cy + y));
+        (x / n, y / n)
+    }
+
+    /// how much the fingers closed (positive) or spread (negative) relative to how far apart they started
+    fn scale(&self) -> f64 {
+        let spread = |current: bool| {
+            let (cx, cy) = self.centroid(current);
+            self.points.iter()
+                .map(|p| if current { p.now } else { p.start })
+                .map(|(x, y)| (x - cx).hypot(y - cy))
+                .sum::<f64>()
+        };
+        let start = spread(false);
+        if start == 0.0 {
+            return 0.0;
+        }
+        1.0 - spread(true) / start
+    }
+
+    /// how much the fingers turned around their centroid in degrees, clockwise being positive
+    fn angle(&self) -> f64 {
+        let (sx, sy) = self.centroid(false);
+        let (cx, cy) = self.centroid(true);
+        let n = self.points.len().max(1) as f64;
+        self.points.iter()
+            .map(|p| {
+                let turned = ((p.now.1 - cy).atan2(p.now.0 - cx) - (p.start.1 - sy).atan2(p.start.0 - sx)).to_degrees();
+                // the shortest way around
+                if turned > 180.0 {
+                    turned - 360.0
+                } else if turned < -180.0 {
+                    turned + 360.0
+                } else {
+                    turned
+                }
+            })
+            .sum::<f64>() / n
+    }
+
+    /// a swipe away from the edge the first finger came down on
+    fn edge_swipe(&self) -> Option<ScreenEdge> {
+        let (dx, dy) = self.translation();
+        if dx.hypot(dy) < self.min_distance {
+            return None;
+        }
+        match (&self.edge, SwipeDirection::of_delta(dx, dy, self.angle_tolerance)?) {
+            (Some(ScreenEdge::Left), SwipeDirection::Right) => Some(ScreenEdge::Left),
+            (Some(ScreenEdge::Right), SwipeDirection::Left) => Some(ScreenEdge::Right),
+            (Some(ScreenEdge::Top), SwipeDirection::Down) => Some(ScreenEdge::Top),
+            (Some(ScreenEdge::Bottom), SwipeDirection::Up) => Some(ScreenEdge::Bottom),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Debug for TouchGesture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{:?} fingers = {} edge = {:?} scale = {} angle = {} cancelled? {:?}",
+            self.translation(), self.fingers, self.edge, self.scale(), self.angle(), self.cancelled
         )
     }
 }
 
-#[derive(Debug)]
+/// The mouse moving while its stroke button is held, from when the button is pressed until it is released
+#[derive(Clone)]
+struct StrokeGesture {
+    /// the directions drawn so far, never the same one twice in a row
+    directions: Vec<StrokeDirection>,
+    /// how far the mouse moved since the last direction was drawn
+    dx: f64,
+    dy: f64,
+    min_distance: f64,
+}
+
+impl StrokeGesture {
+    fn new(min_distance: f64) -> StrokeGesture {
+        StrokeGesture {
+            directions: vec![],
+            dx: 0.0,
+            dy: 0.0,
+            min_distance,
+        }
+    }
+
+    fn add(&mut self, dx: f64, dy: f64) {
+        self.dx += dx;
+        self.dy += dy;
+        if self.dx.hypot(self.dy) < self.min_distance {
+            return;
+        }
+        let direction = StrokeDirection::of_delta(self.dx, self.dy);
+        if self.directions.last() != Some(&direction) {
+            self.directions.push(direction);
+        }
+        self.dx = 0.0;
+        self.dy = 0.0;
+    }
+}
+
+impl fmt::Debug for StrokeGesture {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?} remaining = ({}, {})", self.directions, self.dx, self.dy)
+    }
+}
+
+/// A recognized gesture along with the name of the device it was made on
+#[derive(Debug, Clone)]
+pub struct Gesture {
+    pub gesture_type: GestureType,
+    pub device: String,
+}
+
+impl Gesture {
+    pub fn new(gesture_type: GestureType, device: &str) -> Gesture {
+        Gesture { gesture_type, device: device.to_owned() }
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum GestureType {
-    Swipe(SwipeDirection, i32),
-    Rotation(RotationDirection, f64),
-    Pinch(PinchDirection, f64),
+    Swipe(SwipeDirection, i32, Option<SwipeSpeed>, Option<SwipeLength>),
+    Rotation(RotationDirection, f64, i32),
+    Pinch(PinchDirection, f64, i32),
+    Hold(i32, u64),
+    /// a sequence of gestures, as configured (ie: `swipe.up.3,swipe.left.3`)
+    Sequence(String),
+    /// a swipe from the edge of a touchscreen into it
+    Edge(ScreenEdge, i32),
+    /// a mouse stroke drawn while holding a button (ie: up then right)
+    Stroke(Vec<StrokeDirection>),
+    /// a gesture made while holding modifier keys, in the order of `Modifier`
+    Modified(Vec<Modifier>, Box<GestureType>),
+    /// a switch being toggled on (ie: the lid being closed) or off
+    Switch(Switch, bool),
+    /// a tablet pad button being pressed
+    PadButton(u32),
+    /// a finger going around a tablet pad ring, by ring
+    PadRing(RingDirection, u32),
+    /// a finger sliding on a tablet pad strip, by strip
+    PadStrip(StripDirection, u32),
 }
 
 impl GestureType {
+    /// The configuration key for this gesture, two finger pinches and rotations are the plain `pinch.in` while others
+    /// include their finger count (ie: `pinch.3.in`). Likewise the first ring or strip of a tablet pad is the plain
+    /// `pad.ring.cw` and the others include their number (ie: `pad.ring.1.cw`)
     pub fn to_config(&self) -> String {
         match self {
-            GestureType::Swipe(direction, fingers) => format!("swipe.{}.{}", direction, fingers),
-            GestureType::Rotation(direction, _) => format!("rotation.{}", direction),
-            GestureType::Pinch(direction, _) => format!("pinch.{}", direction),
+            GestureType::Swipe(direction, fingers, _, _) => format!("swipe.{}.{}", direction, fingers),
+            GestureType::Rotation(direction, _, fingers) if *fingers > 2 => format!("rotation.{}.{}", fingers, direction),
+            GestureType::Rotation(direction, _, _) => format!("rotation.{}", direction),
+            GestureType::Pinch(direction, _, fingers) if *fingers > 2 => format!("pinch.{}.{}", fingers, direction),
+            GestureType::Pinch(direction, _, _) => format!("pinch.{}", direction),
+            GestureType::Hold(fingers, _) => format!("hold.{}", fingers),
+            GestureType::Sequence(sequence) => format!("sequence.{}", sequence),
+            GestureType::Edge(edge, fingers) => format!("touch.edge.{}.{}", edge, fingers),
+            GestureType::Stroke(directions) =>
+                format!("mouse.{}", directions.iter().map(|d| d.to_string()).collect::<String>()),
+            GestureType::Modified(modifiers, gesture) => format!("{}.{}", Modifier::prefix(modifiers), gesture.to_config()),
+            GestureType::Switch(switch, on) => format!("switch.{}.{}", switch, switch.state(*on)),
+            GestureType::PadButton(button) => format!("pad.button.{}", button),
+            GestureType::PadRing(direction, ring) if *ring > 0 => format!("pad.ring.{}.{}", ring, direction),
+            GestureType::PadRing(direction, _) => format!("pad.ring.{}", direction),
+            GestureType::PadStrip(direction, strip) if *strip > 0 => format!("pad.strip.{}.{}", strip, direction),
+            GestureType::PadStrip(direction, _) => format!("pad.strip.{}", direction),
+        }
+    }
+
+    /// The configuration keys for this gesture from the most specific to the least specific (ie: `swipe.up.fast.3`,
+    /// `swipe.up.long.3` and then `swipe.up.3` or `pinch.3.in` and then `pinch.in`)
+    ///
+    /// qualifiers go before the finger count because `swipe.up.3` can't be both an action and a table in toml
+    ///
+    /// gestures made while holding modifiers fall back to the ones without (ie: `ctrl.swipe.up.3` and then
+    /// `swipe.up.3`)
+    pub fn to_configs(&self) -> Vec<String> {
+        match self {
+            GestureType::Swipe(direction, fingers, speed, length) => {
+                let qualifiers = [speed.as_ref().map(|s| s.to_string()), length.as_ref().map(|l| l.to_string())];
+                let mut configs: Vec<String> = qualifiers.iter().flatten()
+                    .map(|qualifier| format!("swipe.{}.{}.{}", direction, qualifier, fingers))
+                    .collect();
+                configs.push(self.to_config());
+                configs
+            }
+            GestureType::Rotation(direction, _, fingers) if *fingers > 2 =>
+                vec![self.to_config(), format!("rotation.{}", direction)],
+            GestureType::Pinch(direction, _, fingers) if *fingers > 2 =>
+                vec![self.to_config(), format!("pinch.{}", direction)],
+            GestureType::PadRing(direction, ring) if *ring > 0 =>
+                vec![self.to_config(), format!("pad.ring.{}", direction)],
+            GestureType::PadStrip(direction, strip) if *strip > 0 =>
+                vec![self.to_config(), format!("pad.strip.{}", direction)],
+            GestureType::Modified(modifiers, gesture) => {
+                let prefix = Modifier::prefix(modifiers);
+                let unmodified = gesture.to_configs();
+                let mut configs: Vec<String> = unmodified.iter().map(|c| format!("{}.{}", prefix, c)).collect();
+                configs.extend(unmodified);
+                configs
+            }
+            _ => vec![self.to_config()]
         }
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub enum SwipeDirection {
     Up,
     Down,
     Left,
     Right,
+    UpLeft,
+    UpRight,
+    DownLeft,
+    DownRight,
+}
+
+impl SwipeDirection {
+    /// the direction of a movement, one that strays less than `angle_tolerance` degrees from the
+    /// horizontal or vertical axis is left/right or up/down, anything else is a diagonal
+    fn of_delta(dx: f64, dy: f64, angle_tolerance: f64) -> Option<SwipeDirection> {
+        if dx == 0.0 && dy == 0.0 {
+            return None;
+        }
+
+        // angle of the movement to the horizontal axis, between 0 (horizontal) and 90 (vertical)
+        let angle = dy.abs().atan2(dx.abs()).to_degrees();
+
+        let up = dy < 0.0;
+        let left = dx < 0.0;
+
+        if angle > 90.0 - angle_tolerance {
+            return if up {
+                Some(SwipeDirection::Up)
+            } else {
+                Some(SwipeDirection::Down)
+            };
+        } else if angle < angle_tolerance {
+            return if left {
+                Some(SwipeDirection::Left)
+            } else {
+                Some(SwipeDirection::Right)
+            };
+        }
+        match (up, left) {
+            (true, true) => Some(SwipeDirection::UpLeft),
+            (true, false) => Some(SwipeDirection::UpRight),
+            (false, true) => Some(SwipeDirection::DownLeft),
+            (false, false) => Some(SwipeDirection::DownRight),
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub enum SwipeSpeed {
+    Slow,
+    Fast,
+}
+
+#[derive(Debug, Clone)]
+pub enum SwipeLength {
+    Short,
+    Long,
+}
+
+#[derive(Debug, Clone)]
 pub enum RotationDirection {
     Left,
     Right,
 }
 
 impl RotationDirection {
-    fn of_angle(angle: f64) -> Option<RotationDirection> {
-        if angle > 50.0 {
+    fn of_angle(angle: f64, rotation_angle: f64) -> Option<RotationDirection> {
+        if angle > rotation_angle {
             return Some(RotationDirection::Right)
-        } else if angle < -50.0 {
+        } else if angle < -rotation_angle {
             return Some(RotationDirection::Left)
         }
         None
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
+pub enum ScreenEdge {
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+impl ScreenEdge {
+    /// the edge a point is within `size` of on a `width` by `height` screen, if any
+    fn of_point(x: f64, y: f64, width: f64, height: f64, size: f64) -> Option<ScreenEdge> {
+        if width <= 0.0 || height <= 0.0 {
+            None
+        } else if x < size {
+            Some(ScreenEdge::Left)
+        } else if x > width - size {
+            Some(ScreenEdge::Right)
+        } else if y < size {
+            Some(ScreenEdge::Top)
+        } else if y > height - size {
+            Some(ScreenEdge::Bottom)
+        } else {
+            None
+        }
+    }
+}
+
+/// A straight part of a mouse stroke, there are no diagonals as they are too hard to draw apart from the
+/// straight directions by hand
+#[derive(Debug, Clone, PartialEq)]
+pub enum StrokeDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl StrokeDirection {
+    /// the direction a movement mostly goes in
+    fn of_delta(dx: f64, dy: f64) -> StrokeDirection {
+        if dx.abs() >= dy.abs() {
+            if dx < 0.0 { StrokeDirection::Left } else { StrokeDirection::Right }
+        } else if dy < 0.0 {
+            StrokeDirection::Up
+        } else {
+            StrokeDirection::Down
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum RingDirection {
+    /// clockwise
+    Cw,
+    /// counterclockwise
+    Ccw,
+}
+
+#[derive(Debug, Clone)]
+pub enum StripDirection {
+    Up,
+    Down,
+}
+
+/// A switch of a laptop or convertible
+#[derive(Debug, Clone, PartialEq)]
+pub enum Switch {
+    Lid,
+    /// whether a convertible is folded into a tablet
+    Tablet,
+}
+
+impl Switch {
+    /// how the state of the switch is configured (ie: `close` for the lid)
+    pub fn state(&self, on: bool) -> &'static str {
+        match (self, on) {
+            (Switch::Lid, true) => "close",
+            (Switch::Lid, false) => "open",
+            (Switch::Tablet, true) => "on",
+            (Switch::Tablet, false) => "off",
+        }
+    }
+}
+
+/// A modifier key held while making a gesture, either the left or the right one
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Modifier {
+    Ctrl,
+    Alt,
+    Shift,
+    Super,
+}
+
+impl Modifier {
+    /// the modifier of an evdev key code (ie: 29 for `KEY_LEFTCTRL`), if it is one
+    pub(crate) fn of_key(key: u32) -> Option<Modifier> {
+        match key {
+            29 | 97 => Some(Modifier::Ctrl),
+            56 | 100 => Some(Modifier::Alt),
+            42 | 54 => Some(Modifier::Shift),
+            125 | 126 => Some(Modifier::Super),
+            _ => None,
+        }
+    }
+
+    /// the configuration prefix of `modifiers` (ie: `ctrl.shift`)
+    fn prefix(modifiers: &[Modifier]) -> String {
+        modifiers.iter().map(|m| m.to_string()).collect::<Vec<String>>().join(".")
+    }
+}
+
+#[derive(Debug, Clone)]
 pub enum PinchDirection {
     In,
     Out,
@@ -206,71 +886,251 @@ impl fmt::Display for SwipeDirection {
     }
 }
 
-impl fmt::Display for RotationDirection {
+impl fmt::Display for SwipeSpeed {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", format!("{:?}", self).to_lowercase())
     }
 }
 
-impl fmt::Display for PinchDirection {
+impl fmt::Display for SwipeLength {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         write!(f, "{}", format!("{:?}", self).to_lowercase())
     }
 }
 
-trait Identifiable {
-    fn gesture_type(&self) -> Option<GestureType>;
+impl fmt::Display for RotationDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
 }
 
-impl Identifiable for SwipeGesture {
-    fn gesture_type(&self) -> Option<GestureType> {
-        if self.cancelled {
-            return None;
-        }
-
-        return match self.direction() {
-            Some(d) => Some(GestureType::Swipe(d, self.fingers)),
-            None => None,
-        };
+impl fmt::Display for ScreenEdge {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
     }
 }
 
-impl Identifiable for PinchGesture {
-    fn gesture_type(&self) -> Option<GestureType> {
-        if !self.cancelled {
-            return match self.rotation_direction() {
-                Some(d) => Some(GestureType::Rotation(d, self.angle)),
-                None => match self.direction() {
-                    Some(d) => Some(GestureType::Pinch(d, self.scale)),
-                    None => None
-                }
-            }
-        }
-        return None;
+/// the first letter of the direction, so a stroke reads as `mouse.ur`
+impl fmt::Display for StrokeDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let letter = match self {
+            StrokeDirection::Up => "u",
+            StrokeDirection::Down => "d",
+            StrokeDirection::Left => "l",
+            StrokeDirection::Right => "r",
+        };
+        write!(f, "{}", letter)
     }
 }
 
-struct SwipeBuilder {
-    swipe: Option<SwipeGesture>,
+impl fmt::Display for RingDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
 }
 
-impl SwipeBuilder {
-    fn empty() -> SwipeBuilder {
-        SwipeBuilder { swipe: None }
+impl fmt::Display for StripDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
     }
+}
 
-    fn new(&mut self, fingers: i32) {
-        self.swipe = Some(SwipeGesture::new(fingers));
+impl fmt::Display for Switch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
     }
+}
 
-    fn update(&mut self, event: GestureSwipeUpdateEvent) {
-        match self.swipe {
-            Some(ref mut g) => g.add(event.dx(), event.dy()),
-            None => (),
-        }
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
     }
+}
 
-    fn build(&mut self, event: GestureSwipeEndEvent) -> Result<SwipeGesture, String> {
+impl fmt::Display for PinchDirection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", format!("{:?}", self).to_lowercase())
+    }
+}
+
+/// What the recognizer made of a gesture once it ended or triggered mid-gesture
+#[derive(Debug, Clone)]
+pub enum Decision {
+    Recognized(GestureType),
+    /// why the gesture was not recognized (ie: `unknown direction`)
+    Rejected(&'static str),
+}
+
+trait Identifiable {
+    fn gesture_type(&self) -> Option<GestureType>;
+
+    /// why this is not a gesture when `gesture_type` is `None`
+    fn rejection(&self) -> &'static str;
+
+    fn decision(&self) -> Decision {
+        match self.gesture_type() {
+            Some(t) => Decision::Recognized(t),
+            None => Decision::Rejected(self.rejection()),
+        }
+    }
+}
+
+impl Identifiable for SwipeGesture {
+    fn gesture_type(&self) -> Option<GestureType> {
+        if self.cancelled.is_some() {
+            return None;
+        }
+
+        return match self.direction() {
+            Some(d) => Some(GestureType::Swipe(d, self.fingers, self.speed(), self.length())),
+            None => None,
+        };
+    }
+
+    fn rejection(&self) -> &'static str {
+        self.cancelled.unwrap_or("unknown direction")
+    }
+}
+
+impl Identifiable for HoldGesture {
+    fn gesture_type(&self) -> Option<GestureType> {
+        if self.cancelled.is_some() {
+            return None;
+        }
+        Some(GestureType::Hold(self.fingers, self.duration()))
+    }
+
+    fn rejection(&self) -> &'static str {
+        self.cancelled.unwrap_or("unknown")
+    }
+}
+
+impl Identifiable for PinchGesture {
+    fn gesture_type(&self) -> Option<GestureType> {
+        if self.cancelled.is_none() {
+            return match self.rotation_direction() {
+                Some(d) => Some(GestureType::Rotation(d, self.angle, self.fingers)),
+                None => match self.direction() {
+                    Some(d) => Some(GestureType::Pinch(d, self.scale, self.fingers)),
+                    None => None
+                }
+            }
+        }
+        return None;
+    }
+
+    fn rejection(&self) -> &'static str {
+        self.cancelled.unwrap_or("unknown direction")
+    }
+}
+
+impl Identifiable for TouchGesture {
+    fn gesture_type(&self) -> Option<GestureType> {
+        if self.cancelled.is_some() {
+            return None;
+        }
+        if let Some(edge) = self.edge_swipe() {
+            return Some(GestureType::Edge(edge, self.fingers));
+        }
+        // a single finger taps, scrolls and drags in the application it touches
+        if self.fingers < 2 {
+            return None;
+        }
+
+        let angle = self.angle();
+        if let Some(d) = RotationDirection::of_angle(angle, self.rotation_angle) {
+            return Some(GestureType::Rotation(d, angle, self.fingers));
+        }
+        let scale = self.scale();
+        if scale.abs() >= self.pinch_scale {
+            return PinchDirection::of_scale(scale).map(|d| GestureType::Pinch(d, scale, self.fingers));
+        }
+        let (dx, dy) = self.translation();
+        if dx.hypot(dy) < self.min_distance {
+            return None;
+        }
+        SwipeDirection::of_delta(dx, dy, self.angle_tolerance).map(|d| GestureType::Swipe(d, self.fingers, None, None))
+    }
+
+    fn rejection(&self) -> &'static str {
+        match self.cancelled {
+            Some(reason) => reason,
+            None if self.fingers < 2 => "single finger",
+            None => "shorter than minimum distance",
+        }
+    }
+}
+
+impl Identifiable for StrokeGesture {
+    fn gesture_type(&self) -> Option<GestureType> {
+        if self.directions.is_empty() {
+            return None;
+        }
+        Some(GestureType::Stroke(self.directions.clone()))
+    }
+
+    fn rejection(&self) -> &'static str {
+        "no stroke"
+    }
+}
+
+struct SwipeBuilder {
+    swipe: Option<SwipeGesture>,
+    angle_tolerance: f64,
+    min_distance: f64,
+    fast_velocity: f64,
+    long_distance: f64,
+    continuous_steps: HashMap<String, f64>,
+    triggered: bool,
+}
+
+impl SwipeBuilder {
+    fn empty(angle_tolerance: f64, min_distance: f64, fast_velocity: f64, long_distance: f64,
+             continuous_steps: HashMap<String, f64>) -> SwipeBuilder {
+        SwipeBuilder {
+            swipe: None,
+            angle_tolerance,
+            min_distance,
+            fast_velocity,
+            long_distance,
+            continuous_steps,
+            triggered: false,
+        }
+    }
+
+    fn start(&mut self, fingers: i32, time: u64) {
+        self.swipe = Some(SwipeGesture::new(fingers, self.angle_tolerance, self.fast_velocity, self.long_distance, time));
+        self.triggered = false;
+    }
+
+    /// continuous swipes are triggered (and start over) every time they travel their configured step
+    fn update(&mut self, dx: f64, dy: f64, time: u64) -> Option<SwipeGesture> {
+        let continuous_steps = &self.continuous_steps;
+        match self.swipe {
+            Some(ref mut g) => {
+                g.add(dx, dy, time);
+
+                if continuous_steps.is_empty() || g.distance() == 0.0 {
+                    return None
+                }
+
+                let step = g.gesture_type()
+                    .and_then(|t| continuous_steps.get(&t.to_config()))
+                    .filter(|step| g.distance() >= **step);
+
+                if step.is_some() {
+                    let triggered = *g;
+                    g.reset();
+                    self.triggered = true;
+                    return Some(triggered)
+                }
+                None
+            }
+            None => None,
+        }
+    }
+
+    fn build(&mut self, cancelled: bool) -> Result<SwipeGesture, String> {
 
         // here we dont use copy semantics we simply consume the gesture and reset state on builder
         let mut swipe: Option<SwipeGesture> = None;
@@ -278,8 +1138,14 @@ impl SwipeBuilder {
 
         match swipe {
             Some(mut g) => {
-                if event.cancelled() {
-                    g.cancel();
+                if cancelled {
+                    g.cancel("cancelled");
+                } else if self.triggered {
+                    // whatever is left of a continuous swipe after it triggered is not a swipe on its own
+                    g.cancel("triggered mid-gesture");
+                } else if g.distance() < self.min_distance {
+                    debug!("swipe shorter than minimum distance {}: {:?}", self.min_distance, g);
+                    g.cancel("shorter than minimum distance");
                 }
                 Ok(g)
             }
@@ -291,38 +1157,60 @@ impl SwipeBuilder {
 struct PinchBuilder {
     pinch: Option<PinchGesture>,
     pinch_in_scale_trigger: f64,
-    pinch_out_scale_trigger: f64
+    pinch_out_scale_trigger: f64,
+    rotation_angle: f64,
+    rotation_continuous: bool,
+    fingers: i32,
+    /// whether a rotation was triggered mid-gesture
+    rotated: bool,
 }
 
 impl PinchBuilder {
-    fn empty(pinch_in_scale_trigger: f64, pinch_out_scale_trigger: f64) -> PinchBuilder {
+    fn empty(pinch_in_scale_trigger: f64, pinch_out_scale_trigger: f64, rotation_angle: f64, rotation_continuous: bool)
+        -> PinchBuilder {
         PinchBuilder {
             pinch: None,
             pinch_in_scale_trigger,
             pinch_out_scale_trigger,
+            rotation_angle,
+            rotation_continuous,
+            fingers: 2,
+            rotated: false,
         }
     }
 
-    fn new(&mut self, scale: f64) {
-        self.pinch = Some(PinchGesture::new(scale));
+    fn start(&mut self, fingers: i32, scale: f64) {
+        self.pinch = Some(PinchGesture::new(fingers, scale, self.rotation_angle));
+        self.fingers = fingers;
+        self.rotated = false;
+    }
+
+    /// start over after a gesture was triggered mid-gesture, so it can trigger again
+    fn rearm(&mut self, scale: f64) {
+        self.pinch = Some(PinchGesture::new(self.fingers, scale, self.rotation_angle));
     }
 
-    fn update(&mut self, event: &GesturePinchUpdateEvent) -> Option<PinchGesture> {
+    fn update(&mut self, dx: f64, dy: f64, angle: f64, scale: f64) -> Option<PinchGesture> {
         match self.pinch {
-            Some(mut g) => {
-                g.add(event.dx(), event.dy(), event.angle_delta(), event.scale());
+            Some(ref mut g) => {
+                g.add(dx, dy, angle, scale);
+
+                if self.rotation_continuous && g.is_rotation() {
+                    self.rotated = true;
+                    return Some(*g)
+                }
 
                 match g.direction() {
                     Some(PinchDirection::In) => {
                         // debug!("pinch in variation: {}", g.scale);
                         if self.pinch_in_scale_trigger != 0.0 && g.scale >= self.pinch_in_scale_trigger {
-                            return Some(g)
+                            return Some(*g)
                         }
                     },
                     Some(PinchDirection::Out) => {
                         // debug!("pinch out variation: {}", g.scale);
                         if self.pinch_out_scale_trigger != 0.0 && g.scale <= self.pinch_out_scale_trigger {
-                            return Some(g)
+                            return Some(*g)
                         }
                     },
                     None => return None
@@ -333,7 +1221,7 @@ impl PinchBuilder {
         }
     }
 
-    fn build(&mut self, event: GesturePinchEndEvent) -> Result<PinchGesture, String> {
+    fn build(&mut self, cancelled: bool) -> Result<PinchGesture, String> {
 
         // here we dont use copy semantics we simply consume the gesture and reset state on builder
         let mut pinch: Option<PinchGesture> = None;
@@ -341,8 +1229,54 @@ impl PinchBuilder {
 
         match pinch {
             Some(mut g) => {
-                if event.cancelled() {
-                    g.cancel();
+                // whatever is left after rotating mid-gesture is not a gesture on its own
+                if cancelled {
+                    g.cancel("cancelled");
+                } else if self.rotated {
+                    g.cancel("rotated mid-gesture");
+                }
+                Ok(g)
+            }
+            None => Err("failed to produce event".to_owned()),
+        }
+    }
+}
+
+struct HoldBuilder {
+    hold: Option<HoldGesture>,
+    min_duration: f64,
+}
+
+impl HoldBuilder {
+    fn empty(min_duration: f64) -> HoldBuilder {
+        HoldBuilder {
+            hold: None,
+            min_duration,
+        }
+    }
+
+    fn start(&mut self, fingers: i32, time: u64) {
+        self.hold = Some(HoldGesture::new(fingers, time));
+    }
+
+    fn build(&mut self, cancelled: bool, time: u64) -> Result<HoldGesture, String> {
+
+        // here we dont use copy semantics we simply consume the gesture and reset state on builder
+        let mut hold: Option<HoldGesture> = None;
+        swap(&mut hold, &mut self.hold);
+
+        match hold {
+            Some(mut g) => {
+                g.end(time);
+                if cancelled {
+                    // fingers started moving so this is turning into a swipe or pinch
+                    g.cancel("cancelled");
+                } else if g.fingers < 2 {
+                    debug!("single finger hold is just a finger resting on the touchpad: {:?}", g);
+                    g.cancel("single finger");
+                } else if (g.duration() as f64) < self.min_duration {
+                    debug!("hold shorter than minimum duration {}: {:?}", self.min_duration, g);
+                    g.cancel("shorter than minimum duration");
                 }
                 Ok(g)
             }
@@ -351,78 +1285,1057 @@ impl PinchBuilder {
     }
 }
 
-struct GestureFactory {
+struct TouchBuilder {
+    touch: Option<TouchGesture>,
+    angle_tolerance: f64,
+    min_distance: f64,
+    pinch_scale: f64,
+    edge_size: f64,
+    rotation_angle: f64,
+}
+
+impl TouchBuilder {
+    fn empty(angle_tolerance: f64, min_distance: f64, pinch_scale: f64, edge_size: f64, rotation_angle: f64)
+        -> TouchBuilder {
+        TouchBuilder {
+            touch: None,
+            angle_tolerance,
+            min_distance,
+            pinch_scale,
+            edge_size,
+            rotation_angle,
+        }
+    }
+
+    /// a finger coming down, starting a new gesture if it is the first one
+    fn down(&mut self, slot: u32, x: f64, y: f64, width: f64, height: f64) {
+        if self.touch.is_none() {
+            let edge = ScreenEdge::of_point(x, y, width, height, self.edge_size);
+            self.touch = Some(TouchGesture::new(edge, self.angle_tolerance, self.min_distance, self.pinch_scale,
+                                                self.rotation_angle));
+        }
+        if let Some(touch) = self.touch.as_mut() {
+            touch.down(slot, x, y);
+        }
+    }
+
+    fn motion(&mut self, slot: u32, x: f64, y: f64) {
+        if let Some(touch) = self.touch.as_mut() {
+            touch.motion(slot, x, y);
+        }
+    }
+
+    /// a finger being lifted, or cancelled, ending the gesture once it is the last one
+    fn up(&mut self, slot: u32, cancelled: bool) -> Option<TouchGesture> {
+        let touch = self.touch.as_mut()?;
+        touch.up(slot);
+        if cancelled {
+            touch.cancel("cancelled");
+        }
+        if !touch.down.is_empty() {
+            return None;
+        }
+        self.touch.take()
+    }
+}
+
+struct StrokeBuilder {
+    stroke: Option<StrokeGesture>,
+    /// strokes are not drawn without a button
+    button: Option<u32>,
+    min_distance: f64,
+}
+
+impl StrokeBuilder {
+    fn empty(button: Option<u32>, min_distance: f64) -> StrokeBuilder {
+        StrokeBuilder {
+            stroke: None,
+            button,
+            min_distance,
+        }
+    }
+
+    /// a mouse button being pressed or released, ending the stroke when it is the stroke button being released
+    fn button(&mut self, button: u32, pressed: bool) -> Option<StrokeGesture> {
+        if Some(button) != self.button {
+            return None;
+        }
+        if pressed {
+            self.stroke = Some(StrokeGesture::new(self.min_distance));
+            return None;
+        }
+        self.stroke.take()
+    }
+
+    fn motion(&mut self, dx: f64, dy: f64) {
+        if let Some(stroke) = self.stroke.as_mut() {
+            stroke.add(dx, dy);
+        }
+    }
+}
+
+/// Where a finger last was on a tablet pad ring or strip and how far it went since it last triggered
+#[derive(Default)]
+struct PadAxis {
+    position: f64,
+    travelled: f64,
+}
+
+struct PadBuilder {
+    /// the rings and strips being touched, by number
+    rings: HashMap<u32, PadAxis>,
+    strips: HashMap<u32, PadAxis>,
+    ring_step: f64,
+    strip_step: f64,
+}
+
+impl PadBuilder {
+    fn empty(ring_step: f64, strip_step: f64) -> PadBuilder {
+        PadBuilder {
+            rings: HashMap::new(),
+            strips: HashMap::new(),
+            ring_step,
+            strip_step,
+        }
+    }
+
+    /// how far the finger went on `axis` once it went a whole `step` in either direction, positions below 0
+    /// being the finger lifted
+    fn travel(axes: &mut HashMap<u32, PadAxis>, axis: u32, position: f64, step: f64, wrap: Option<f64>)
+        -> Option<f64> {
+        if position < 0.0 {
+            axes.remove(&axis);
+            return None;
+        }
+        let axis = axes.entry(axis).or_insert(PadAxis { position, travelled: 0.0 });
+        let mut delta = position - axis.position;
+        // the shortest way around a ring, from 359 to 1 degrees is 2 degrees and not -358
+        if let Some(full) = wrap {
+            if delta > full / 2.0 {
+                delta -= full;
+            } else if delta < -full / 2.0 {
+                delta += full;
+            }
+        }
+        axis.position = position;
+        axis.travelled += delta;
+        if axis.travelled.abs() < step {
+            return None;
+        }
+        Some(take(&mut axis.travelled))
+    }
+
+    fn ring(&mut self, ring: u32, position: f64) -> Option<GestureType> {
+        let travelled = PadBuilder::travel(&mut self.rings, ring, position, self.ring_step, Some(360.0))?;
+        // ring positions go clockwise
+        let direction = if travelled > 0.0 { RingDirection::Cw } else { RingDirection::Ccw };
+        Some(GestureType::PadRing(direction, ring))
+    }
+
+    fn strip(&mut self, strip: u32, position: f64) -> Option<GestureType> {
+        let travelled = PadBuilder::travel(&mut self.strips, strip, position, self.strip_step, None)?;
+        let direction = if travelled > 0.0 { StripDirection::Down } else { StripDirection::Up };
+        Some(GestureType::PadStrip(direction, strip))
+    }
+}
+
+/// The builders recognizing gestures with the triggers of a device
+struct Recognizers {
     swipe: SwipeBuilder,
     pinch: PinchBuilder,
+    hold: HoldBuilder,
+    touch: TouchBuilder,
+    stroke: StrokeBuilder,
+    pad: PadBuilder,
+}
+
+impl Recognizers {
+    fn new(triggers: &GestureTriggers) -> Recognizers {
+        Recognizers {
+            swipe: SwipeBuilder::empty(triggers.swipe_angle_tolerance, triggers.swipe_min_distance,
+                                       triggers.swipe_fast_velocity, triggers.swipe_long_distance,
+                                       triggers.continuous_swipe_steps.clone()),
+            pinch: PinchBuilder::empty(triggers.pinch_in_scale, triggers.pinch_out_scale, triggers.rotation_angle,
+                                       triggers.rotation_continuous),
+            hold: HoldBuilder::empty(triggers.hold_min_duration),
+            touch: TouchBuilder::empty(triggers.swipe_angle_tolerance, triggers.touch_min_distance,
+                                       triggers.touch_pinch_scale, triggers.touch_edge_size, triggers.rotation_angle),
+            stroke: StrokeBuilder::empty(triggers.mouse_button, triggers.mouse_min_distance),
+            pad: PadBuilder::empty(triggers.pad_ring_step, triggers.pad_strip_step),
+        }
+    }
+}
+
+/// The modifier keys held down on any keyboard of the seat
+#[derive(Default)]
+struct ModifierKeys {
+    /// the key codes held, so that releasing one of two held shift keys still leaves shift held
+    held: HashSet<u32>,
+}
+
+impl ModifierKeys {
+    fn key(&mut self, key: u32, pressed: bool) {
+        if pressed {
+            self.held.insert(key);
+        } else {
+            self.held.remove(&key);
+        }
+    }
+
+    /// `gesture_type` qualified by the modifiers held, if any. Switches are not as they are not made by hand
+    fn qualify(&self, gesture_type: GestureType) -> GestureType {
+        let mut modifiers: Vec<Modifier> = self.held.iter().filter_map(|key| Modifier::of_key(*key)).collect();
+        if modifiers.is_empty() || matches!(gesture_type, GestureType::Switch(_, _)) {
+            return gesture_type;
+        }
+        modifiers.sort();
+        modifiers.dedup();
+        GestureType::Modified(modifiers, Box::new(gesture_type))
+    }
+}
+
+pub(crate) struct GestureFactory {
+    recognizers: Recognizers,
+    /// recognizers of the devices with triggers of their own, keyed by their lowercased name
+    devices: HashMap<String, Recognizers>,
+    cooldown: Cooldown,
+    modifiers: ModifierKeys,
 }
 
 impl GestureFactory {
-    pub fn new(pinch_in_scale_trigger: f64, pinch_out_scale_trigger: f64) -> GestureFactory {
+    pub fn new(triggers: GestureTriggers) -> GestureFactory {
         GestureFactory {
-            swipe: SwipeBuilder::empty(),
-            pinch: PinchBuilder::empty(pinch_in_scale_trigger, pinch_out_scale_trigger),
+            recognizers: Recognizers::new(&triggers),
+            devices: triggers.devices.iter()
+                .map(|(device, device_triggers)| (device.to_owned(), Recognizers::new(device_triggers)))
+                .collect(),
+            cooldown: Cooldown::new(triggers.cooldown, triggers.cooldowns),
+            modifiers: ModifierKeys::default(),
         }
     }
 
-    pub fn event(&mut self, event: input::Event) -> Option<GestureType> {
-        match event {
-            Gesture(Swipe(Begin(event))) =>
-                self.swipe.new(event.finger_count()),
-            Gesture(Swipe(Update(event))) => {
-                self.swipe.update(event);
+    pub fn event(&mut self, event: RawGestureEvent) -> Option<Gesture> {
+        match self.decide(&event) {
+            Some(Decision::Recognized(gesture_type)) => Some(Gesture::new(gesture_type, event.device())),
+            _ => None,
+        }
+    }
+
+    /// the decision on the gesture `event` ends or triggers, if it does
+    pub fn decide(&mut self, event: &RawGestureEvent) -> Option<Decision> {
+        match self.recognize(event) {
+            Some(Decision::Recognized(t)) => {
+                let t = self.modifiers.qualify(t);
+                if !self.cooldown.allow(&t, event.time()) {
+                    return Some(Decision::Rejected("cooldown"));
+                }
+                Some(Decision::Recognized(t))
             }
-            Gesture(Swipe(End(event))) => {
-                match self.swipe.build(event) {
-                    Ok(g) => match g.gesture_type() {
-                        Some(t) => return Some(t),
-                        None => warn!("cancelled or unrecognized gesture {:?}", g),
-                    },
+            decision => decision,
+        }
+    }
+
+    fn recognizers(&mut self, device: &str) -> &mut Recognizers {
+        if self.devices.is_empty() {
+            return &mut self.recognizers;
+        }
+        match self.devices.get_mut(&device.to_lowercase()) {
+            Some(recognizers) => recognizers,
+            None => &mut self.recognizers,
+        }
+    }
+
+    fn recognize(&mut self, event: &RawGestureEvent) -> Option<Decision> {
+        let r = self.recognizers(event.device());
+        match *event {
+            RawGestureEvent::SwipeBegin { fingers, time, .. } =>
+                r.swipe.start(fingers, time),
+            RawGestureEvent::SwipeUpdate { dx, dy, time, .. } => {
+                if let Some(g) = r.swipe.update(dx, dy, time) {
+                    return Some(g.decision());
+                }
+            }
+            RawGestureEvent::SwipeEnd { cancelled, .. } => {
+                match r.swipe.build(cancelled) {
+                    Ok(g) => {
+                        if g.gesture_type().is_none() {
+                            warn!("cancelled or unrecognized gesture {:?}", g);
+                        }
+                        return Some(g.decision());
+                    }
                     Err(s) => error!("no Gesture {:?}", s),
                 }
             }
 
-            Gesture(Pinch(GesturePinchEvent::Begin(event))) =>
-                self.pinch.new(event.scale()),
-            Gesture(Pinch(GesturePinchEvent::Update(event))) => {
-                if let Some(p) = self.pinch.update(&event) {
+            RawGestureEvent::PinchBegin { fingers, scale, .. } =>
+                r.pinch.start(fingers, scale),
+            RawGestureEvent::PinchUpdate { dx, dy, scale, angle, .. } => {
+                if let Some(p) = r.pinch.update(dx, dy, angle, scale) {
                     if !p.gesture_type().is_some() {
                         warn!("cancelled or unrecognized gesture {:?}", p);
                     }
-                    self.pinch.new(event.scale());
-                    return p.gesture_type();
+                    r.pinch.rearm(scale);
+                    return Some(p.decision());
                 }
             }
-            Gesture(Pinch(GesturePinchEvent::End(event))) => {
-                match self.pinch.build(event) {
-                    Ok(p) => match p.gesture_type() {
-                        Some(t) => return Some(t),
-                        None => warn!("cancelled or unrecognized gesture {:?}", p),
-                    },
+            RawGestureEvent::PinchEnd { cancelled, .. } => {
+                match r.pinch.build(cancelled) {
+                    Ok(p) => {
+                        if p.gesture_type().is_none() {
+                            warn!("cancelled or unrecognized gesture {:?}", p);
+                        }
+                        return Some(p.decision());
+                    }
                     Err(s) => error!("no Gesture {:?}", s),
                 }
             }
 
-            _ => (),
+            RawGestureEvent::HoldBegin { fingers, time, .. } =>
+                r.hold.start(fingers, time),
+            RawGestureEvent::HoldEnd { cancelled, time, .. } => {
+                match r.hold.build(cancelled, time) {
+                    Ok(h) => {
+                        if h.gesture_type().is_none() {
+                            debug!("cancelled or unrecognized gesture {:?}", h);
+                        }
+                        return Some(h.decision());
+                    }
+                    Err(s) => error!("no Gesture {:?}", s),
+                }
+            }
+
+            RawGestureEvent::TouchDown { slot, x, y, width, height, .. } =>
+                r.touch.down(slot, x, y, width, height),
+            RawGestureEvent::TouchMotion { slot, x, y, .. } =>
+                r.touch.motion(slot, x, y),
+            RawGestureEvent::TouchUp { slot, .. } | RawGestureEvent::TouchCancel { slot, .. } => {
+                let cancelled = matches!(event, RawGestureEvent::TouchCancel { .. });
+                if let Some(t) = r.touch.up(slot, cancelled) {
+                    // most touches are taps and scrolls with a single finger
+                    if t.gesture_type().is_none() {
+                        debug!("cancelled or unrecognized gesture {:?}", t);
+                    }
+                    return Some(t.decision());
+                }
+            }
+
+            RawGestureEvent::PointerButton { button, pressed, .. } => {
+                if let Some(s) = r.stroke.button(button, pressed) {
+                    // a click without moving is left to the application as it is
+                    if s.gesture_type().is_none() {
+                        debug!("cancelled or unrecognized gesture {:?}", s);
+                    }
+                    return Some(s.decision());
+                }
+            }
+            RawGestureEvent::PointerMotion { dx, dy, .. } =>
+                r.stroke.motion(dx, dy),
+
+            RawGestureEvent::Key { key, pressed, .. } =>
+                self.modifiers.key(key, pressed),
+
+            RawGestureEvent::Switch { ref switch, on, .. } =>
+                return Some(Decision::Recognized(GestureType::Switch(switch.clone(), on))),
+
+            RawGestureEvent::PadButton { button, pressed: true, .. } =>
+                return Some(Decision::Recognized(GestureType::PadButton(button))),
+            RawGestureEvent::PadButton { pressed: false, .. } => {}
+            RawGestureEvent::PadRing { ring, position, .. } => {
+                if let Some(t) = r.pad.ring(ring, position) {
+                    return Some(Decision::Recognized(t));
+                }
+            }
+            RawGestureEvent::PadStrip { strip, position, .. } => {
+                if let Some(t) = r.pad.strip(strip, position) {
+                    return Some(Decision::Recognized(t));
+                }
+            }
         }
         None
     }
 }
 
-/// Open a channel that will produce identified gestures from gesticle
-pub fn gesture_channel(pinch_in_scale_trigger: f64, pinch_out_scale_trigger: f64) -> mpsc::Receiver<GestureType> {
+/// Stops the thread reading input events for a `GestureChannel`, when stopped or dropped
+pub struct ChannelHandle {
+    stop: Arc<OwnedFd>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl ChannelHandle {
+    /// stop reading input events, closing libinput, and wait for the thread to finish
+    pub fn stop(&mut self) {
+        if let Some(thread) = self.thread.take() {
+            wake(&self.stop);
+            if thread.join().is_err() {
+                error!("gesture thread panicked");
+            }
+        }
+    }
+}
+
+impl Drop for ChannelHandle {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
 
-    let (tx, rx) = mpsc::channel();
+fn wake(fd: &OwnedFd) {
+    if let Err(e) = write(fd.as_raw_fd(), &1u64.to_ne_bytes()) {
+        error!("failed to stop gesture channel: {:?}", e);
+    }
+}
+
+/// The receiving end of a `GestureChannel`, input events stop being read once it is dropped
+pub struct GestureReceiver {
+    gestures: mpsc::Receiver<Gesture>,
+    stop: Arc<OwnedFd>,
+}
 
-    thread::spawn(move || {
+impl Deref for GestureReceiver {
+    type Target = mpsc::Receiver<Gesture>;
 
-        let mut factory = GestureFactory::new(pinch_in_scale_trigger, pinch_out_scale_trigger);
+    fn deref(&self) -> &mpsc::Receiver<Gesture> {
+        &self.gestures
+    }
+}
+
+impl Drop for GestureReceiver {
+    fn drop(&mut self) {
+        wake(&self.stop);
+    }
+}
+
+/// Identified gestures from gesticle, input events stop being read once this is stopped or dropped
+pub struct GestureChannel {
+    gestures: GestureReceiver,
+    handle: ChannelHandle,
+}
 
-        input_events(&mut |e| {
-            if let Some(gesture) = factory.event(e) {
-                tx.send(gesture).unwrap();
+impl GestureChannel {
+    pub fn recv(&self) -> Result<Gesture, mpsc::RecvError> {
+        self.gestures.recv()
+    }
+
+    pub fn stop(&mut self) {
+        self.handle.stop();
+    }
+
+    /// the receiving end of the channel and the handle that stops it, ie: to pass the gestures on to
+    /// `sequence_channel`. Dropping either of them stops the thread reading input events
+    pub fn split(self) -> (GestureReceiver, ChannelHandle) {
+        (self.gestures, self.handle)
+    }
+}
+
+impl Iterator for GestureChannel {
+    type Item = Gesture;
+
+    fn next(&mut self) -> Option<Gesture> {
+        self.gestures.recv().ok()
+    }
+}
+
+/// Open a channel that will produce identified gestures from gesticle, made on the devices of the seat in
+/// `input`. Channels for different seats can be open at the same time
+pub fn gesture_channel(input: InputSettings, triggers: GestureTriggers) -> error::Result<GestureChannel> {
+    open_gesture_channel(input, triggers, None)
+}
+
+/// Open a channel like `gesture_channel` that also writes every gesture event, and what was decided on
+/// them, to `trace`
+pub fn recorded_gesture_channel(input: InputSettings, triggers: GestureTriggers, trace: TraceWriter<File>)
+    -> error::Result<GestureChannel> {
+    open_gesture_channel(input, triggers, Some(trace))
+}
+
+fn open_gesture_channel(input: InputSettings, triggers: GestureTriggers, mut trace: Option<TraceWriter<File>>)
+    -> error::Result<GestureChannel> {
+
+    spawn_channel(move |tx, stop, opened| {
+
+        // libinput can not be sent to other threads so it is opened here, letting the caller know how it went
+        let mut libinput = match open_libinput(&input) {
+            Ok(libinput) => {
+                let _ = opened.send(Ok(()));
+                libinput
+            }
+            Err(e) => {
+                let _ = opened.send(Err(e));
+                return;
+            }
+        };
+        let mut factory = GestureFactory::new(triggers);
+
+        let result = dispatch_until(&mut libinput, &input, &mut |e| {
+            let event = match RawGestureEvent::from_libinput(e) {
+                Some(event) => event,
+                None => return,
+            };
+            if let Some(trace) = trace.as_mut() {
+                trace.event(&event);
+            }
+            let decision = factory.decide(&event);
+            if let (Some(trace), Some(d)) = (trace.as_mut(), &decision) {
+                trace.decision(d, &event);
             }
-        });
-    });
+            if let Some(Decision::Recognized(gesture_type)) = decision {
+                if tx.send(Gesture::new(gesture_type, event.device())).is_err() {
+                    debug!("nobody is receiving gestures anymore");
+                    wake(&stop);
+                }
+            }
+        }, &[stop.as_raw_fd()]);
+
+        match result {
+            Ok(_) => debug!("stopped reading input events"),
+            Err(e) => error!("{}", e),
+        }
+    })
+}
+
+/// Run `work` on a thread of its own, sending the gestures it recognizes until the eventfd it is given becomes
+/// readable. It lets the caller know whether it could start reading input events before doing so
+fn spawn_channel<F>(work: F) -> error::Result<GestureChannel>
+    where F: FnOnce(mpsc::Sender<Gesture>, Arc<OwnedFd>, mpsc::SyncSender<error::Result<()>>) + Send + 'static {
+
+    let (tx, rx) = mpsc::channel();
+    let (opened_tx, opened_rx) = mpsc::sync_channel(1);
+    let stop = eventfd(0, EFD_CLOEXEC).map_err(|e| Error::Input(format!("failed to create eventfd: {:?}", e)))?;
+    // closed once the thread, the receiver and the handle are all done with it
+    let stop = Arc::new(unsafe { OwnedFd::from_raw_fd(stop) });
+
+    let thread_stop = stop.clone();
+    let thread = thread::spawn(move || work(tx, thread_stop, opened_tx));
 
-    return rx
+    let handle = ChannelHandle { stop: stop.clone(), thread: Some(thread) };
+    match opened_rx.recv() {
+        Ok(Ok(())) => Ok(GestureChannel { gestures: GestureReceiver { gestures: rx, stop }, handle }),
+        Ok(Err(e)) => Err(e),
+        Err(_) => Err(Error::Input("gesture thread stopped before opening libinput".to_owned())),
+    }
+}
+
+/// The gestures recognized from a recorded stream of events (ie: from `libinput record`)
+pub fn replay(events: Vec<RawGestureEvent>, triggers: GestureTriggers) -> Vec<Gesture> {
+    let mut factory = GestureFactory::new(triggers);
+    events.into_iter()
+        .filter_map(|e| factory.event(e))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use std::os::unix::io::AsRawFd;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    use nix::unistd::pipe;
+
+    use crate::configuration::GestureActions;
+    use crate::events::{RawGestureEvent, wait};
+    use crate::gestures::{spawn_channel, Cooldown, Gesture, GestureChannel, GestureFactory, GestureTriggers, GestureType, HoldGesture, Identifiable, Modifier, PinchDirection, PinchGesture, RotationDirection,
+                          SwipeDirection, SwipeGesture, SwipeLength, SwipeSpeed, Switch, TouchGesture};
+
+    fn swipe(dx: f64, dy: f64) -> Option<SwipeDirection> {
+        swipe_with_tolerance(dx, dy, 15.0)
+    }
+
+    fn swipe_with_tolerance(dx: f64, dy: f64, angle_tolerance: f64) -> Option<SwipeDirection> {
+        let mut swipe = SwipeGesture::new(3, angle_tolerance, 0.0, 0.0, 0);
+        swipe.add(dx, dy, 10_000);
+        swipe.direction()
+    }
+
+    #[test]
+    fn swipe_directions() {
+        assert!(matches!(swipe(0.0, -100.0), Some(SwipeDirection::Up)));
+        assert!(matches!(swipe(10.0, 100.0), Some(SwipeDirection::Down)));
+        assert!(matches!(swipe(-100.0, 0.0), Some(SwipeDirection::Left)));
+        assert!(matches!(swipe(100.0, -10.0), Some(SwipeDirection::Right)));
+        assert!(matches!(swipe(-100.0, -100.0), Some(SwipeDirection::UpLeft)));
+        assert!(matches!(swipe(100.0, -60.0), Some(SwipeDirection::UpRight)));
+        assert!(matches!(swipe(-60.0, 100.0), Some(SwipeDirection::DownLeft)));
+        assert!(matches!(swipe(100.0, 100.0), Some(SwipeDirection::DownRight)));
+        assert!(swipe(0.0, 0.0).is_none());
+    }
+
+    #[test]
+    fn swipe_angle_tolerance() {
+        assert!(matches!(swipe_with_tolerance(100.0, -30.0, 15.0), Some(SwipeDirection::UpRight)));
+        assert!(matches!(swipe_with_tolerance(100.0, -30.0, 20.0), Some(SwipeDirection::Right)));
+        assert!(matches!(swipe_with_tolerance(30.0, 100.0, 20.0), Some(SwipeDirection::Down)));
+    }
+
+    #[test]
+    fn swipe_speed() {
+        let mut swipe = SwipeGesture::new(3, 15.0, 2.0, 0.0, 0);
+        swipe.add(0.0, -10.0, 10_000);
+        swipe.add(0.0, -10.0, 20_000);
+        assert!(matches!(swipe.gesture_type(), Some(GestureType::Swipe(SwipeDirection::Up, 3, Some(SwipeSpeed::Slow), None))));
+        assert_eq!(swipe.duration(), 20);
+
+        swipe.add(0.0, -30.0, 30_000);
+        assert!(matches!(swipe.gesture_type(), Some(GestureType::Swipe(SwipeDirection::Up, 3, Some(SwipeSpeed::Fast), None))));
+        assert_eq!(swipe.gesture_type().unwrap().to_configs(), vec!["swipe.up.fast.3", "swipe.up.3"]);
+
+        let mut swipe = SwipeGesture::new(3, 15.0, 0.0, 0.0, 0);
+        swipe.add(0.0, -100.0, 10_000);
+        assert!(matches!(swipe.gesture_type(), Some(GestureType::Swipe(SwipeDirection::Up, 3, None, None))));
+        assert_eq!(swipe.gesture_type().unwrap().to_configs(), vec!["swipe.up.3"]);
+    }
+
+    #[test]
+    fn swipe_length() {
+        let mut swipe = SwipeGesture::new(4, 15.0, 0.0, 100.0, 0);
+        swipe.add(-60.0, 0.0, 10_000);
+        assert!(matches!(swipe.gesture_type(), Some(GestureType::Swipe(SwipeDirection::Left, 4, None, Some(SwipeLength::Short)))));
+
+        swipe.add(-60.0, 0.0, 20_000);
+        assert!(matches!(swipe.gesture_type(), Some(GestureType::Swipe(SwipeDirection::Left, 4, None, Some(SwipeLength::Long)))));
+        assert_eq!(swipe.gesture_type().unwrap().to_configs(), vec!["swipe.left.long.4", "swipe.left.4"]);
+    }
+
+    #[test]
+    fn hold() {
+        let mut hold = HoldGesture::new(3, 1_000_000);
+        hold.end(1_700_000);
+        assert!(matches!(hold.gesture_type(), Some(GestureType::Hold(3, 700))));
+        assert_eq!(hold.gesture_type().unwrap().to_configs(), vec!["hold.3"]);
+
+        hold.cancel("cancelled");
+        assert!(hold.gesture_type().is_none());
+    }
+
+    #[test]
+    fn rotation_angle() {
+        let mut pinch = PinchGesture::new(2, 1.0, 30.0);
+        pinch.add(0.0, 0.0, 20.0, 1.0);
+        assert!(pinch.rotation_direction().is_none());
+        pinch.add(0.0, 0.0, 15.0, 1.0);
+        assert!(matches!(pinch.rotation_direction(), Some(RotationDirection::Right)));
+        assert!(pinch.direction().is_none());
+
+        let mut pinch = PinchGesture::new(2, 1.0, 30.0);
+        pinch.add(0.0, 0.0, -35.0, 1.0);
+        assert!(matches!(pinch.rotation_direction(), Some(RotationDirection::Left)));
+    }
+
+    #[test]
+    fn pinch_fingers() {
+        let mut pinch = PinchGesture::new(2, 1.0, 50.0);
+        pinch.add(0.0, 0.0, 0.0, 0.5);
+        assert!(matches!(pinch.gesture_type(), Some(GestureType::Pinch(PinchDirection::In, _, 2))));
+        assert_eq!(pinch.gesture_type().unwrap().to_configs(), vec!["pinch.in"]);
+
+        let mut pinch = PinchGesture::new(3, 1.0, 50.0);
+        pinch.add(0.0, 0.0, 0.0, 1.5);
+        assert_eq!(pinch.gesture_type().unwrap().to_configs(), vec!["pinch.3.out", "pinch.out"]);
+
+        let mut pinch = PinchGesture::new(4, 1.0, 50.0);
+        pinch.add(0.0, 0.0, -60.0, 1.0);
+        assert_eq!(pinch.gesture_type().unwrap().to_configs(), vec!["rotation.4.left", "rotation.left"]);
+    }
+
+    #[test]
+    fn cooldown() {
+        let cooldowns = HashMap::from([("swipe.left.3".to_owned(), 0.0), ("pinch.in".to_owned(), 1000.0)]);
+        let mut cooldown = Cooldown::new(300.0, cooldowns);
+        let swipe_up = GestureType::Swipe(SwipeDirection::Up, 3, None, None);
+        let swipe_left = GestureType::Swipe(SwipeDirection::Left, 3, None, None);
+        let pinch_in = GestureType::Pinch(PinchDirection::In, 0.0, 3);
+
+        assert!(cooldown.allow(&swipe_up, 1_000_000));
+        assert!(!cooldown.allow(&swipe_up, 1_200_000));
+        assert!(cooldown.allow(&swipe_up, 1_400_000));
+        assert!(cooldown.allow(&GestureType::Swipe(SwipeDirection::Up, 4, None, None), 1_500_000));
+
+        assert!(cooldown.allow(&swipe_left, 1_000_000));
+        assert!(cooldown.allow(&swipe_left, 1_000_001));
+
+        // falls back to the `pinch.in` cooldown
+        assert!(cooldown.allow(&pinch_in, 1_000_000));
+        assert!(!cooldown.allow(&pinch_in, 1_500_000));
+        assert!(cooldown.allow(&pinch_in, 2_000_000));
+    }
+
+    fn factory(settings: &[(&str, f64)]) -> GestureFactory {
+        let mut config = config::Config::new();
+        for (key, value) in settings {
+            config.set(key, *value).unwrap();
+        }
+        GestureFactory::new(GestureTriggers::from_config(&GestureActions::new_with_config(config)))
+    }
+
+    /// the configuration of the gestures triggered by `events`
+    fn events(factory: &mut GestureFactory, events: Vec<RawGestureEvent>) -> Vec<String> {
+        events.into_iter()
+            .filter_map(|e| factory.event(e))
+            .map(|g| g.gesture_type.to_config())
+            .collect()
+    }
+
+    /// a swipe with updates 10ms apart
+    fn swipe_events(fingers: i32, moves: &[(f64, f64)], cancelled: bool) -> Vec<RawGestureEvent> {
+        swipe_events_on("touchpad", fingers, moves, cancelled)
+    }
+
+    fn swipe_events_on(device: &str, fingers: i32, moves: &[(f64, f64)], cancelled: bool) -> Vec<RawGestureEvent> {
+        let device = device.to_owned();
+        let mut events = vec![RawGestureEvent::SwipeBegin { fingers, time: 0, device: device.clone() }];
+        let mut time = 0;
+        for (dx, dy) in moves {
+            time += 10_000;
+            events.push(RawGestureEvent::SwipeUpdate { dx: *dx, dy: *dy, time, device: device.clone() });
+        }
+        events.push(RawGestureEvent::SwipeEnd { cancelled, time: time + 10_000, device });
+        events
+    }
+
+    fn pinch_events(fingers: i32, updates: &[(f64, f64)], cancelled: bool) -> Vec<RawGestureEvent> {
+        let device = "touchpad".to_owned();
+        let mut events = vec![RawGestureEvent::PinchBegin { fingers, scale: 1.0, time: 0, device: device.clone() }];
+        let mut time = 0;
+        let mut scale = 1.0;
+        for (angle, s) in updates {
+            time += 10_000;
+            scale = *s;
+            events.push(RawGestureEvent::PinchUpdate { dx: 0.0, dy: 0.0, scale, angle: *angle, time, device: device.clone() });
+        }
+        events.push(RawGestureEvent::PinchEnd { cancelled, scale, time: time + 10_000, device });
+        events
+    }
+
+    #[test]
+    fn factory_swipe() {
+        let mut factory = factory(&[("gesture.trigger.swipe.min_distance", 50.0)]);
+
+        assert_eq!(events(&mut factory, swipe_events(3, &[(0.0, -30.0), (2.0, -30.0)], false)), vec!["swipe.up.3"]);
+        assert_eq!(events(&mut factory, swipe_events(4, &[(30.0, 30.0), (30.0, 30.0)], false)), vec!["swipe.downright.4"]);
+        assert!(events(&mut factory, swipe_events(3, &[(-30.0, 0.0), (-30.0, 0.0)], true)).is_empty());
+        assert!(events(&mut factory, swipe_events(3, &[(-20.0, 0.0), (-20.0, 0.0)], false)).is_empty());
+        assert!(events(&mut factory, swipe_events(3, &[], false)).is_empty());
+    }
+
+    #[test]
+    fn factory_continuous_swipe() {
+        let mut factory = factory(&[("gesture.trigger.continuous.swipe.left.3", 100.0)]);
+
+        let moves = [(-30.0, 0.0); 8];
+        assert_eq!(events(&mut factory, swipe_events(3, &moves, false)), vec!["swipe.left.3", "swipe.left.3"]);
+        assert_eq!(events(&mut factory, swipe_events(3, &[(-30.0, 0.0)], false)), vec!["swipe.left.3"]);
+        assert_eq!(events(&mut factory, swipe_events(4, &moves, false)), vec!["swipe.left.4"]);
+    }
+
+    #[test]
+    fn factory_pinch() {
+        let mut factory = factory(&[]);
+        assert_eq!(events(&mut factory, pinch_events(2, &[(0.0, 0.9), (0.0, 0.5)], false)), vec!["pinch.in"]);
+        assert_eq!(events(&mut factory, pinch_events(3, &[(0.0, 1.5)], false)), vec!["pinch.3.out"]);
+        assert!(events(&mut factory, pinch_events(2, &[(0.0, 0.5)], true)).is_empty());
+        assert_eq!(events(&mut factory, pinch_events(2, &[(30.0, 1.0), (30.0, 1.0), (30.0, 1.0)], false)),
+                   vec!["rotation.right"]);
+    }
+
+    #[test]
+    fn factory_pinch_trigger() {
+        let mut factory = factory(&[("gesture.trigger.pinch.in.scale", 0.2), ("gesture.trigger.pinch.out.scale", -0.5)]);
+
+        // triggers mid-gesture and again once it went on by the same scale
+        assert_eq!(events(&mut factory, pinch_events(2, &[(0.0, 0.9), (0.0, 0.75), (0.0, 0.6), (0.0, 0.5)], false)),
+                   vec!["pinch.in", "pinch.in"]);
+        // pinches that do not reach their trigger are still gestures when they end
+        assert_eq!(events(&mut factory, pinch_events(2, &[(0.0, 1.2), (0.0, 1.4)], false)), vec!["pinch.out"]);
+        assert_eq!(events(&mut factory, pinch_events(2, &[(0.0, 1.2), (0.0, 1.6)], false)), vec!["pinch.out"]);
+    }
+
+    #[test]
+    fn factory_rotation_continuous() {
+        let twist = || pinch_events(2, &[(20.0, 1.0), (20.0, 1.0), (20.0, 1.0), (20.0, 1.0)], false);
+        let mut factory = factory(&[("gesture.trigger.rotation.angle", 30.0)]);
+        assert_eq!(events(&mut factory, twist()), vec!["rotation.right"]);
+
+        let mut config = config::Config::new();
+        config.set("gesture.trigger.rotation.angle", 30.0).unwrap();
+        config.set("gesture.trigger.rotation.continuous", true).unwrap();
+        let mut factory = GestureFactory::new(GestureTriggers::from_config(&GestureActions::new_with_config(config)));
+        // every 30 degrees, what is left when the fingers are lifted is not a rotation of its own
+        assert_eq!(events(&mut factory, twist()), vec!["rotation.right", "rotation.right"]);
+        // pinches still trigger when they end
+        assert_eq!(events(&mut factory, pinch_events(2, &[(0.0, 0.9), (0.0, 0.5)], false)), vec!["pinch.in"]);
+    }
+
+    #[test]
+    fn factory_hold() {
+        let mut factory = factory(&[]);
+        let hold = |fingers: i32, duration: u64, cancelled: bool| vec![
+            RawGestureEvent::HoldBegin { fingers, time: 0, device: "touchpad".to_owned() },
+            RawGestureEvent::HoldEnd { cancelled, time: duration * 1000, device: "touchpad".to_owned() },
+        ];
+
+        assert_eq!(events(&mut factory, hold(3, 600, false)), vec!["hold.3"]);
+        assert!(events(&mut factory, hold(3, 400, false)).is_empty());
+        assert!(events(&mut factory, hold(3, 600, true)).is_empty());
+        assert!(events(&mut factory, hold(1, 600, false)).is_empty());
+    }
+
+    #[test]
+    fn factory_cooldown() {
+        let mut factory = factory(&[("gesture.trigger.cooldown.default", 300.0)]);
+        let mut swipes = swipe_events(3, &[(0.0, -30.0)], false);
+        swipes.extend(swipe_events(3, &[(0.0, -30.0)], false));
+
+        assert_eq!(events(&mut factory, swipes), vec!["swipe.up.3"]);
+    }
+
+    /// where a finger comes down and where it is lifted
+    type TouchMove = ((f64, f64), (f64, f64));
+
+    /// fingers coming down on a 300x200mm touchscreen, moving from where they start to where they end and lifted
+    fn touch_events(moves: &[TouchMove], cancelled: bool) -> Vec<RawGestureEvent> {
+        let device = "touchscreen".to_owned();
+        let mut events = vec![];
+        for (slot, ((x, y), _)) in moves.iter().enumerate() {
+            events.push(RawGestureEvent::TouchDown {
+                slot: slot as u32, x: *x, y: *y, width: 300.0, height: 200.0, time: 0, device: device.clone(),
+            });
+        }
+        for (slot, (_, (x, y))) in moves.iter().enumerate() {
+            events.push(RawGestureEvent::TouchMotion { slot: slot as u32, x: *x, y: *y, time: 10_000, device: device.clone() });
+        }
+        for slot in 0..moves.len() as u32 {
+            events.push(if cancelled {
+                RawGestureEvent::TouchCancel { slot, time: 20_000, device: device.clone() }
+            } else {
+                RawGestureEvent::TouchUp { slot, time: 20_000, device: device.clone() }
+            });
+        }
+        events
+    }
+
+    #[test]
+    fn factory_touch() {
+        let mut factory = factory(&[]);
+
+        let swipe = [((100.0, 100.0), (60.0, 100.0)), ((120.0, 100.0), (80.0, 100.0))];
+        assert_eq!(events(&mut factory, touch_events(&swipe, false)), vec!["swipe.left.2"]);
+        assert!(events(&mut factory, touch_events(&swipe, true)).is_empty());
+
+        let spread = [((100.0, 100.0), (80.0, 100.0)), ((140.0, 100.0), (160.0, 100.0))];
+        assert_eq!(events(&mut factory, touch_events(&spread, false)), vec!["pinch.out"]);
+
+        let clockwise = [((100.0, 100.0), (120.0, 80.0)), ((140.0, 100.0), (120.0, 120.0))];
+        assert_eq!(events(&mut factory, touch_events(&clockwise, false)), vec!["rotation.right"]);
+
+        let three = [((100.0, 100.0), (100.0, 60.0)), ((120.0, 100.0), (120.0, 60.0)), ((140.0, 100.0), (140.0, 60.0))];
+        assert_eq!(events(&mut factory, touch_events(&three, false)), vec!["swipe.up.3"]);
+
+        // single fingers are left to the application, unless they come in from an edge
+        assert!(events(&mut factory, touch_events(&[((100.0, 100.0), (150.0, 100.0))], false)).is_empty());
+        assert_eq!(events(&mut factory, touch_events(&[((2.0, 100.0), (50.0, 100.0))], false)), vec!["touch.edge.left.1"]);
+        assert_eq!(events(&mut factory, touch_events(&[((150.0, 198.0), (150.0, 150.0))], false)),
+                   vec!["touch.edge.bottom.1"]);
+        assert!(events(&mut factory, touch_events(&[((2.0, 100.0), (2.0, 150.0))], false)).is_empty());
+    }
+
+    #[test]
+    fn angle_tolerance() {
+        let tolerance = |t: f64| {
+            let mut config = config::Config::new();
+            config.set("gesture.trigger.swipe.angle_tolerance", t).unwrap();
+            GestureTriggers::from_config(&GestureActions::new_with_config(config)).swipe_angle_tolerance
+        };
+        assert_eq!(tolerance(20.0), 20.0);
+        assert_eq!(tolerance(60.0), 45.0);
+        assert_eq!(tolerance(-5.0), 0.0);
+    }
+
+    #[test]
+    fn touch_slots() {
+        let mut touch = TouchGesture::new(None, 15.0, 20.0, 0.2, 50.0);
+        touch.down(0, 100.0, 100.0);
+        touch.down(1, 120.0, 100.0);
+        touch.motion(0, 60.0, 100.0);
+        // a slot that is not down is left alone
+        touch.up(7);
+        touch.up(0);
+        assert_eq!(touch.down.len(), 1);
+
+        // the next finger on a lifted slot is a new touch point, the lifted one stays where it was lifted
+        touch.down(0, 200.0, 150.0);
+        touch.motion(0, 210.0, 150.0);
+        assert_eq!(touch.points.iter().map(|p| (p.start, p.now)).collect::<Vec<_>>(), vec![
+            ((100.0, 100.0), (60.0, 100.0)),
+            ((120.0, 100.0), (120.0, 100.0)),
+            ((200.0, 150.0), (210.0, 150.0)),
+        ]);
+        assert_eq!(touch.fingers, 2);
+
+        touch.up(0);
+        touch.up(0);
+        assert_eq!(touch.down.len(), 1);
+        touch.up(1);
+        assert!(touch.down.is_empty());
+    }
+
+    /// a stroke button press, the mouse moving by `moves` 10ms apart and the button being released
+    fn mouse_events(button: u32, moves: &[(f64, f64)]) -> Vec<RawGestureEvent> {
+        let device = "mouse".to_owned();
+        let mut events = vec![RawGestureEvent::PointerButton { button, pressed: true, time: 0, device: device.clone() }];
+        let mut time = 0;
+        for (dx, dy) in moves {
+            time += 10_000;
+            events.push(RawGestureEvent::PointerMotion { dx: *dx, dy: *dy, time, device: device.clone() });
+        }
+        events.push(RawGestureEvent::PointerButton { button, pressed: false, time: time + 10_000, device });
+        events
+    }
+
+    #[test]
+    fn factory_mouse() {
+        let right = 0x111;
+        // no button draws strokes unless one is configured
+        assert!(events(&mut factory(&[]), mouse_events(right, &[(-60.0, 0.0)])).is_empty());
+
+        let mut config = config::Config::new();
+        config.set("gesture.trigger.mouse.button", "right").unwrap();
+        let mut factory = GestureFactory::new(GestureTriggers::from_config(&GestureActions::new_with_config(config)));
+
+        assert_eq!(events(&mut factory, mouse_events(right, &[(-30.0, 2.0), (-30.0, -2.0)])), vec!["mouse.l"]);
+        assert_eq!(events(&mut factory, mouse_events(right, &[(0.0, 60.0), (20.0, 0.0), (20.0, 0.0), (20.0, 0.0)])),
+                   vec!["mouse.dr"]);
+        assert_eq!(events(&mut factory, mouse_events(right, &[(0.0, 60.0), (0.0, 60.0), (-60.0, 5.0)])), vec!["mouse.dl"]);
+        // a click, or a stroke too short to have a direction
+        assert!(events(&mut factory, mouse_events(right, &[])).is_empty());
+        assert!(events(&mut factory, mouse_events(right, &[(10.0, 0.0)])).is_empty());
+        // other buttons drag and select as usual
+        assert!(events(&mut factory, mouse_events(0x110, &[(-60.0, 0.0)])).is_empty());
+        assert!(events(&mut factory, mouse_events(0x112, &[(0.0, -60.0)])).is_empty());
+
+        let mut config = config::Config::new();
+        config.set("gesture.trigger.mouse.button", "middle").unwrap();
+        let mut middle = GestureFactory::new(GestureTriggers::from_config(&GestureActions::new_with_config(config)));
+        assert_eq!(events(&mut middle, mouse_events(0x112, &[(0.0, -60.0)])), vec!["mouse.u"]);
+    }
+
+    #[test]
+    fn factory_modifiers() {
+        let mut factory = factory(&[]);
+        let key = |key: u32, pressed: bool| RawGestureEvent::Key { key, pressed, time: 0, device: "keyboard".to_owned() };
+        let swipe = || swipe_events(3, &[(0.0, -30.0), (0.0, -30.0)], false);
+
+        let ctrl = vec![key(29, true)].into_iter().chain(swipe()).collect();
+        assert_eq!(events(&mut factory, ctrl), vec!["ctrl.swipe.up.3"]);
+
+        // left shift is still held after the right one is released
+        let shift = vec![key(42, true), key(54, true), key(54, false)].into_iter().chain(swipe()).collect();
+        assert_eq!(events(&mut factory, shift), vec!["ctrl.shift.swipe.up.3"]);
+
+        let released = vec![key(29, false), key(42, false)].into_iter().chain(swipe()).collect();
+        assert_eq!(events(&mut factory, released), vec!["swipe.up.3"]);
+    }
+
+    #[test]
+    fn factory_switch() {
+        let mut factory = factory(&[]);
+        let switch = |switch: Switch, on: bool| vec![
+            RawGestureEvent::Key { key: 125, pressed: true, time: 0, device: "keyboard".to_owned() },
+            RawGestureEvent::Switch { switch, on, time: 0, device: "Lid Switch".to_owned() },
+        ];
+
+        assert_eq!(events(&mut factory, switch(Switch::Lid, true)), vec!["switch.lid.close"]);
+        assert_eq!(events(&mut factory, switch(Switch::Lid, false)), vec!["switch.lid.open"]);
+        assert_eq!(events(&mut factory, switch(Switch::Tablet, true)), vec!["switch.tablet.on"]);
+        assert_eq!(events(&mut factory, switch(Switch::Tablet, false)), vec!["switch.tablet.off"]);
+    }
+
+    #[test]
+    fn factory_pad() {
+        let mut factory = factory(&[]);
+        let pad = "Wacom Intuos Pro M Pad".to_owned();
+        let ring = |ring: u32, positions: &[f64]| positions.iter()
+            .map(|position| RawGestureEvent::PadRing { ring, position: *position, time: 0, device: pad.clone() })
+            .collect::<Vec<_>>();
+
+        assert_eq!(events(&mut factory, vec![
+            RawGestureEvent::PadButton { button: 3, pressed: true, time: 0, device: pad.clone() },
+            RawGestureEvent::PadButton { button: 3, pressed: false, time: 0, device: pad.clone() },
+        ]), vec!["pad.button.3"]);
+
+        // clockwise across the top of the ring, then back counterclockwise and lifted before the next step
+        assert_eq!(events(&mut factory, ring(0, &[350.0, 358.0, 5.0, 10.0, 355.0, 340.0, -1.0])),
+                   vec!["pad.ring.cw", "pad.ring.ccw"]);
+        assert_eq!(events(&mut factory, ring(1, &[90.0, 70.0, -1.0])), vec!["pad.ring.1.ccw"]);
+        assert!(events(&mut factory, ring(0, &[90.0, 100.0, -1.0, 110.0, 120.0])).is_empty());
+
+        let strip = [0.5, 0.55, 0.62, 0.7, -1.0].iter()
+            .map(|position| RawGestureEvent::PadStrip { strip: 0, position: *position, time: 0, device: pad.clone() })
+            .collect();
+        assert_eq!(events(&mut factory, strip), vec!["pad.strip.down"]);
+    }
+
+    #[test]
+    fn modified_configs() {
+        let swipe = GestureType::Swipe(SwipeDirection::Up, 3, Some(SwipeSpeed::Fast), None);
+        let modified = GestureType::Modified(vec![Modifier::Ctrl, Modifier::Alt], Box::new(swipe));
+        assert_eq!(modified.to_config(), "ctrl.alt.swipe.up.3");
+        assert_eq!(modified.to_configs(),
+                   vec!["ctrl.alt.swipe.up.fast.3", "ctrl.alt.swipe.up.3", "swipe.up.fast.3", "swipe.up.3"]);
+    }
+
+    #[test]
+    fn factory_devices() {
+        let mut config = config::Config::new();
+        config.merge(config::File::from_str(r#"
+            [gesture.trigger.swipe]
+            min_distance = 50.0
+
+            [device."Apple Magic Trackpad".gesture.trigger.swipe]
+            min_distance = 100.0
+        "#, config::FileFormat::Toml)).unwrap();
+        let mut factory = GestureFactory::new(GestureTriggers::from_config(&GestureActions::new_with_config(config)));
+        let trackpad = "Apple Magic Trackpad";
+        let moves = [(-40.0, 0.0), (-40.0, 0.0)];
+
+        assert_eq!(events(&mut factory, swipe_events(3, &moves, false)), vec!["swipe.left.3"]);
+        assert!(events(&mut factory, swipe_events_on(trackpad, 3, &moves, false)).is_empty());
+
+        let gesture = swipe_events_on(trackpad, 3, &[(-60.0, 0.0), (-60.0, 0.0)], false).into_iter()
+            .find_map(|e| factory.event(e))
+            .unwrap();
+        assert_eq!(gesture.gesture_type.to_config(), "swipe.left.3");
+        assert_eq!(gesture.device, trackpad);
+    }
+
+    /// a channel sending a single hold and then waiting to be stopped, letting `done` know once it is
+    fn waiting_channel(done: mpsc::Sender<()>) -> GestureChannel {
+        spawn_channel(move |tx, stop, opened| {
+            // input events that never come
+            let (input, _events) = pipe().unwrap();
+            tx.send(Gesture::new(GestureType::Hold(3, 600), "touchpad")).unwrap();
+            opened.send(Ok(())).unwrap();
+            while wait(input, &[stop.as_raw_fd()]).unwrap().is_none() {}
+            done.send(()).unwrap();
+        }).unwrap()
+    }
+
+    #[test]
+    fn channel_stop() {
+        let (done, stopped) = mpsc::channel();
+        let (gestures, mut handle) = waiting_channel(done).split();
+        assert_eq!(gestures.recv().unwrap().gesture_type.to_config(), "hold.3");
+
+        handle.stop();
+        assert!(stopped.try_recv().is_ok());
+        assert!(gestures.recv().is_err());
+    }
+
+    #[test]
+    fn channel_receiver_dropped() {
+        let (done, stopped) = mpsc::channel();
+        let (gestures, _handle) = waiting_channel(done).split();
+
+        drop(gestures);
+        assert!(stopped.recv_timeout(Duration::from_secs(1)).is_ok());
+    }
 }
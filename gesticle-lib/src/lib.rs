@@ -1,9 +1,19 @@
 #[macro_use]
 extern crate log;
 
+pub mod error;
+
+pub use error::{Error, Result};
+
 pub mod configuration;
 pub mod events;
+pub mod devices;
 pub mod gestures;
+pub mod sequences;
+pub mod replay;
+pub mod trace;
+#[cfg(feature = "stream")]
+pub mod stream;
 pub mod dbus;
 
 #[cfg(test)]
@@ -0,0 +1,192 @@
+use std::fs;
+use std::path::Path;
+
+use yaml_rust::{Yaml, YamlLoader};
+
+use crate::error::{Error, Result};
+use crate::events::RawGestureEvent;
+use crate::gestures::{Modifier, Switch};
+
+/// Read the gesture events of a `libinput record` trace
+pub fn read_record<P: AsRef<Path>>(path: P) -> Result<Vec<RawGestureEvent>> {
+    let path = path.as_ref();
+    let yaml = fs::read_to_string(path).map_err(|e| Error::Io(format!("failed to read {}", path.display()), e))?;
+    parse_record(&yaml)
+}
+
+/// Parse the gesture events of a `libinput record` trace, ordered by time. Every other event is ignored. The
+/// trace must be recorded with `--with-libinput`, without it only the kernel events are written
+pub fn parse_record(yaml: &str) -> Result<Vec<RawGestureEvent>> {
+    let docs = YamlLoader::load_from_str(yaml).map_err(|e| Error::Trace(e.to_string()))?;
+    let doc = docs.first().ok_or_else(|| Error::Trace("empty record".to_owned()))?;
+    let devices = doc["devices"].as_vec().ok_or_else(|| Error::Trace("no devices in record".to_owned()))?;
+
+    let mut events = vec![];
+    let mut with_libinput = false;
+    for device in devices {
+        let name = device["evdev"]["name"].as_str().unwrap_or("unknown");
+        // only touchpads, touchscreens and tablets have a size
+        let size = &device["libinput"]["size"];
+        let size = (number(&size[0]).unwrap_or(0.0), number(&size[1]).unwrap_or(0.0));
+        let frames = match device["events"].as_vec() {
+            Some(frames) => frames,
+            None => continue,
+        };
+        for frame in frames {
+            if let Some(libinput_events) = frame["libinput"].as_vec() {
+                with_libinput = true;
+                for event in libinput_events {
+                    if let Some(event) = gesture_event(event, name, size)? {
+                        events.push(event);
+                    }
+                }
+            }
+        }
+    }
+    if !with_libinput {
+        return Err(Error::Trace("no libinput events in record, was it recorded with `libinput record --with-libinput`?".to_owned()));
+    }
+    events.sort_by_key(|e| e.time());
+    Ok(events)
+}
+
+fn gesture_event(event: &Yaml, device: &str, (width, height): (f64, f64)) -> Result<Option<RawGestureEvent>> {
+    let event_type = event["type"].as_str().ok_or_else(|| Error::Trace(format!("event without type: {:?}", event)))?;
+    if !["GESTURE_", "TOUCH_", "POINTER_", "KEYBOARD_", "SWITCH_", "TABLET_PAD_"].iter().any(|prefix| event_type.starts_with(prefix)) {
+        return Ok(None);
+    }
+
+    let time = number(&event["time"]).map(|t| (t * 1_000_000.0).round() as u64)
+        .ok_or_else(|| Error::Trace(format!("event without time: {:?}", event)))?;
+    let fingers = event["nfingers"].as_i64().unwrap_or(0) as i32;
+    let dx = number(&event["delta"][0]).unwrap_or(0.0);
+    let dy = number(&event["delta"][1]).unwrap_or(0.0);
+    let scale = number(&event["scale"]).unwrap_or(1.0);
+    let angle = number(&event["angle_delta"]).unwrap_or(0.0);
+    // older versions of libinput do not record whether a gesture was cancelled
+    let cancelled = event["cancelled"].as_bool().unwrap_or(false);
+    let button = event["button"].as_i64().unwrap_or(0) as u32;
+    let pressed = event["state"].as_str() == Some("pressed");
+    let key = key_code(&event["key"]);
+    // which ring or strip of a tablet pad
+    let index = event["number"].as_i64().unwrap_or(0) as u32;
+    let position = number(&event["position"]).unwrap_or(-1.0);
+    let slot = event["seat_slot"].as_i64().unwrap_or(0) as u32;
+    let x = number(&event["point"][0]).unwrap_or(0.0);
+    let y = number(&event["point"][1]).unwrap_or(0.0);
+    let device = device.to_owned();
+
+    let event = match event_type {
+        "GESTURE_SWIPE_BEGIN" => RawGestureEvent::SwipeBegin { fingers, time, device },
+        "GESTURE_SWIPE_UPDATE" => RawGestureEvent::SwipeUpdate { dx, dy, time, device },
+        "GESTURE_SWIPE_END" => RawGestureEvent::SwipeEnd { cancelled, time, device },
+        "GESTURE_PINCH_BEGIN" => RawGestureEvent::PinchBegin { fingers, scale, time, device },
+        "GESTURE_PINCH_UPDATE" => RawGestureEvent::PinchUpdate { dx, dy, scale, angle, time, device },
+        "GESTURE_PINCH_END" => RawGestureEvent::PinchEnd { cancelled, scale, time, device },
+        "GESTURE_HOLD_BEGIN" => RawGestureEvent::HoldBegin { fingers, time, device },
+        "GESTURE_HOLD_END" => RawGestureEvent::HoldEnd { cancelled, time, device },
+        "TOUCH_DOWN" => RawGestureEvent::TouchDown { slot, x, y, width, height, time, device },
+        "TOUCH_MOTION" => RawGestureEvent::TouchMotion { slot, x, y, time, device },
+        "TOUCH_UP" => RawGestureEvent::TouchUp { slot, time, device },
+        "TOUCH_CANCEL" => RawGestureEvent::TouchCancel { slot, time, device },
+        // touch frames only group the touch points that changed together
+        "TOUCH_FRAME" => return Ok(None),
+        "POINTER_BUTTON" => RawGestureEvent::PointerButton { button, pressed, time, device },
+        "POINTER_MOTION" => RawGestureEvent::PointerMotion { dx, dy, time, device },
+        // scrolling and absolute motion (ie: from a tablet) do not draw strokes
+        _ if event_type.starts_with("POINTER_") => return Ok(None),
+        // switches are recorded as their libinput number, lid being 1 and tablet mode 2
+        "SWITCH_TOGGLE" => {
+            let switch = match event["switch"].as_i64() {
+                Some(1) => Switch::Lid,
+                Some(2) => Switch::Tablet,
+                _ => return Ok(None),
+            };
+            RawGestureEvent::Switch { switch, on: event["state"].as_str() == Some("on"), time, device }
+        }
+        "TABLET_PAD_BUTTON" => RawGestureEvent::PadButton { button, pressed, time, device },
+        "TABLET_PAD_RING" => RawGestureEvent::PadRing { ring: index, position, time, device },
+        "TABLET_PAD_STRIP" => RawGestureEvent::PadStrip { strip: index, position, time, device },
+        // only modifier keys are read, like from libinput
+        "KEYBOARD_KEY" => match key.filter(|key| Modifier::of_key(*key).is_some()) {
+            Some(key) => RawGestureEvent::Key { key, pressed, time, device },
+            None => return Ok(None),
+        },
+        _ => {
+            warn!("unknown gesture event in record: {}", event_type);
+            return Ok(None);
+        }
+    };
+    Ok(Some(event))
+}
+
+/// keys are recorded as their code followed by their name (ie: `29 (KEY_LEFTCTRL)`), or just their code
+fn key_code(value: &Yaml) -> Option<u32> {
+    match value {
+        Yaml::Integer(code) => Some(*code as u32),
+        Yaml::String(key) => key.split_whitespace().next()?.parse().ok(),
+        _ => None,
+    }
+}
+
+/// numbers in a record are written as `%6.2f` so whole ones may be parsed as integers
+fn number(value: &Yaml) -> Option<f64> {
+    value.as_f64().or_else(|| value.as_i64().map(|i| i as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configuration::GestureActions;
+    use crate::error::Error;
+    use crate::events::RawGestureEvent;
+    use crate::gestures::{GestureTriggers, replay};
+    use crate::replay::parse_record;
+
+    /// traces along with the gestures they should be recognized as. The `synthetic-` ones are written by hand in the
+    /// format of `libinput record --with-libinput`, captures of real hardware go next to them without the prefix
+    const TRACES: [(&str, &str); 2] = [
+        (include_str!("../tests/traces/synthetic-swipes.yml"), include_str!("../tests/traces/synthetic-swipes.gestures")),
+        (include_str!("../tests/traces/synthetic-pinch-hold.yml"), include_str!("../tests/traces/synthetic-pinch-hold.gestures")),
+    ];
+
+    #[test]
+    fn parse() {
+        let events = parse_record(TRACES[0].0).unwrap();
+        // moving the pointer before swiping, which draws strokes while a mouse button is held
+        assert_eq!(events[0], RawGestureEvent::PointerMotion {
+            dx: 1.25,
+            dy: -0.5,
+            time: 900_000,
+            device: "SynPS/2 Synaptics TouchPad".to_owned(),
+        });
+        assert_eq!(events[1], RawGestureEvent::SwipeBegin {
+            fingers: 3,
+            time: 1_204_300,
+            device: "SynPS/2 Synaptics TouchPad".to_owned(),
+        });
+        assert!(events.windows(2).all(|w| w[0].time() <= w[1].time()));
+        assert!(parse_record("devices: 3").is_err());
+    }
+
+    #[test]
+    fn without_libinput() {
+        // the same trace as recorded without --with-libinput
+        let trace = TRACES[0].0.lines()
+            .filter(|l| !l.trim_start().starts_with("- libinput:") && !l.trim_start().starts_with("- {time:"))
+            .collect::<Vec<&str>>()
+            .join("\n");
+        assert!(matches!(parse_record(&trace), Err(Error::Trace(_))));
+    }
+
+    #[test]
+    fn traces() {
+        let triggers = GestureTriggers::from_config(&GestureActions::new_with_config(config::Config::new()));
+        for (trace, gestures) in TRACES.iter() {
+            let recognized: Vec<String> = replay(parse_record(trace).unwrap(), triggers.clone()).iter()
+                .map(|g| g.gesture_type.to_config())
+                .collect();
+            let expected: Vec<&str> = gestures.lines().filter(|l| !l.is_empty()).collect();
+            assert_eq!(recognized, expected);
+        }
+    }
+}
@@ -0,0 +1,285 @@
+use std::sync::mpsc;
+use std::sync::mpsc::RecvTimeoutError;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::configuration::GestureActions;
+use crate::gestures::{Gesture, GestureReceiver, GestureType};
+
+/// What to do with a gesture that can be the start of a sequence
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum SequencePolicy {
+    /// hold the gesture back and only trigger it if the sequence is not completed in time
+    Delay,
+    /// trigger the gesture right away, and the sequence as well if it gets completed
+    Commit,
+}
+
+/// Sequences of gestures that can be bound to a single action (ie: `swipe.up.3,swipe.left.3`)
+#[derive(Debug, Clone)]
+pub struct Sequences {
+    sequences: Vec<(String, Vec<String>)>,
+    timeout: Duration,
+    policy: SequencePolicy,
+}
+
+impl Sequences {
+    pub fn new(sequences: Vec<String>, timeout: Duration, policy: SequencePolicy) -> Sequences {
+        Sequences {
+            sequences: sequences.into_iter()
+                .map(|s| {
+                    let steps = s.split(',').map(|step| step.trim().to_owned()).collect();
+                    (s, steps)
+                })
+                .collect(),
+            timeout,
+            policy,
+        }
+    }
+
+    /// the sequences bound in `app`, the focused one when its gestures are made
+    pub fn from_config(actions: &GestureActions, app: Option<&str>) -> Sequences {
+        let timeout = actions.get_float("gesture.sequence.timeout").unwrap_or(600.0);
+        let policy = match actions.get("gesture.sequence.policy").as_deref() {
+            Some("commit") => SequencePolicy::Commit,
+            _ => SequencePolicy::Delay,
+        };
+        Sequences::new(actions.sequences_for_app(app), Duration::from_millis(timeout as u64), policy)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.sequences.is_empty()
+    }
+
+    /// the sequence completed by `gestures` and whether they are also the start of a longer sequence
+    fn find(&self, gestures: &[Gesture]) -> (Option<String>, bool) {
+        let mut complete = None;
+        let mut longer = false;
+        for (sequence, steps) in &self.sequences {
+            let matches = gestures.len() <= steps.len() && gestures.iter().zip(steps)
+                .all(|(gesture, step)| gesture.gesture_type.to_configs().contains(step));
+            if matches && gestures.len() == steps.len() {
+                complete = Some(sequence.to_owned());
+            } else if matches {
+                longer = true;
+            }
+        }
+        (complete, longer)
+    }
+}
+
+/// The sequences a gesture can start, read again for every one so they are the ones of the focused app and follow
+/// configuration changes
+pub type SequenceSource = Box<dyn FnMut() -> Sequences + Send>;
+
+struct SequenceMatcher {
+    source: SequenceSource,
+    sequences: Sequences,
+    pending: Vec<Gesture>,
+    deadline: Option<Instant>,
+}
+
+impl SequenceMatcher {
+    fn new(mut source: SequenceSource) -> SequenceMatcher {
+        SequenceMatcher {
+            sequences: source(),
+            source,
+            pending: vec![],
+            deadline: None,
+        }
+    }
+
+    /// the gestures to trigger after `gesture` was recognized at `now`, sequences are made on the device of
+    /// their last gesture
+    fn gesture(&mut self, gesture: Gesture, now: Instant) -> Vec<Gesture> {
+        let mut triggered = vec![];
+        if self.deadline.is_some_and(|deadline| now > deadline) {
+            triggered.append(&mut self.timeout());
+        }
+
+        if self.pending.is_empty() {
+            self.sequences = (self.source)();
+        }
+        self.pending.push(gesture.clone());
+        let (complete, longer) = self.sequences.find(&self.pending);
+
+        if longer {
+            // wait for the rest of the sequence
+            if self.sequences.policy == SequencePolicy::Commit {
+                triggered.push(gesture);
+            }
+            self.deadline = Some(now + self.sequences.timeout);
+        } else if let Some(sequence) = complete {
+            debug!("completed sequence: {}", sequence);
+            self.pending.clear();
+            self.deadline = None;
+            triggered.push(Gesture::new(GestureType::Sequence(sequence), &gesture.device));
+        } else {
+            // not part of the sequence we were waiting on so it can only start a new one, once the pending
+            // gestures are triggered as they would have been on timeout, completing their sequence if they do
+            self.pending.pop();
+            let restart = !self.pending.is_empty();
+            triggered.append(&mut self.timeout());
+            if restart {
+                triggered.append(&mut self.gesture(gesture, now));
+            } else {
+                triggered.push(gesture);
+            }
+        }
+        triggered
+    }
+
+    /// the gestures to trigger once a sequence was not continued in time
+    fn timeout(&mut self) -> Vec<Gesture> {
+        match self.sequences.find(&self.pending) {
+            (Some(sequence), _) => {
+                debug!("completed sequence on timeout: {}", sequence);
+                let device = self.pending.last().map(|g| g.device.to_owned()).unwrap_or_default();
+                self.pending.clear();
+                self.deadline = None;
+                vec![Gesture::new(GestureType::Sequence(sequence), &device)]
+            }
+            (None, _) => self.flush()
+        }
+    }
+
+    fn flush(&mut self) -> Vec<Gesture> {
+        self.deadline = None;
+        let pending: Vec<Gesture> = self.pending.drain(..).collect();
+        match self.sequences.policy {
+            SequencePolicy::Delay => pending,
+            // these were triggered already
+            SequencePolicy::Commit => vec![],
+        }
+    }
+}
+
+/// Turn a channel of gestures into one where the configured sequences of gestures are triggered as
+/// `GestureType::Sequence`
+pub fn sequence_channel(gestures: GestureReceiver, sequences: SequenceSource) -> mpsc::Receiver<Gesture> {
+
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+
+        let mut matcher = SequenceMatcher::new(sequences);
+
+        loop {
+            let received = match matcher.deadline {
+                Some(deadline) => gestures.recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => gestures.recv().map_err(|_| RecvTimeoutError::Disconnected),
+            };
+
+            let triggered = match received {
+                Ok(gesture) => matcher.gesture(gesture, Instant::now()),
+                Err(RecvTimeoutError::Timeout) => matcher.timeout(),
+                Err(RecvTimeoutError::Disconnected) => break,
+            };
+
+            for gesture in triggered {
+                if tx.send(gesture).is_err() {
+                    return;
+                }
+            }
+        }
+    });
+
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, Instant};
+
+    use std::sync::{Arc, Mutex};
+
+    use crate::configuration::GestureActions;
+    use crate::gestures::{Gesture, GestureType, SwipeDirection};
+    use crate::sequences::{SequenceMatcher, SequencePolicy, Sequences};
+
+    fn matcher(policy: SequencePolicy) -> SequenceMatcher {
+        let sequences = vec!["swipe.up.3,swipe.left.3".to_owned(), "swipe.up.3,swipe.left.3,swipe.down.3".to_owned()];
+        SequenceMatcher::new(Box::new(move || Sequences::new(sequences.clone(), Duration::from_millis(600), policy)))
+    }
+
+    fn swipe(direction: SwipeDirection) -> Gesture {
+        Gesture::new(GestureType::Swipe(direction, 3, None, None), "touchpad")
+    }
+
+    fn configs(gestures: Vec<Gesture>) -> Vec<String> {
+        gestures.iter().map(|g| g.gesture_type.to_config()).collect()
+    }
+
+    #[test]
+    fn delay() {
+        let mut matcher = matcher(SequencePolicy::Delay);
+        let now = Instant::now();
+
+        assert!(matcher.gesture(swipe(SwipeDirection::Up), now).is_empty());
+        assert!(matcher.gesture(swipe(SwipeDirection::Left), now).is_empty());
+        assert_eq!(configs(matcher.timeout()), vec!["sequence.swipe.up.3,swipe.left.3"]);
+
+        matcher.gesture(swipe(SwipeDirection::Up), now);
+        matcher.gesture(swipe(SwipeDirection::Left), now);
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Down), now)),
+                   vec!["sequence.swipe.up.3,swipe.left.3,swipe.down.3"]);
+
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Right), now)), vec!["swipe.right.3"]);
+
+        matcher.gesture(swipe(SwipeDirection::Up), now);
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Right), now)), vec!["swipe.up.3", "swipe.right.3"]);
+
+        matcher.gesture(swipe(SwipeDirection::Up), now);
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Left), now + Duration::from_millis(700))),
+                   vec!["swipe.up.3", "swipe.left.3"]);
+
+        // a completed sequence that could have gone on is not lost to the gesture interrupting it
+        matcher.gesture(swipe(SwipeDirection::Up), now);
+        matcher.gesture(swipe(SwipeDirection::Left), now);
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Right), now)),
+                   vec!["sequence.swipe.up.3,swipe.left.3", "swipe.right.3"]);
+    }
+
+    #[test]
+    fn commit() {
+        let mut matcher = matcher(SequencePolicy::Commit);
+        let now = Instant::now();
+
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Up), now)), vec!["swipe.up.3"]);
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Left), now)), vec!["swipe.left.3"]);
+        assert_eq!(configs(matcher.timeout()), vec!["sequence.swipe.up.3,swipe.left.3"]);
+
+        matcher.gesture(swipe(SwipeDirection::Up), now);
+        assert!(matcher.timeout().is_empty());
+
+        matcher.gesture(swipe(SwipeDirection::Up), now);
+        matcher.gesture(swipe(SwipeDirection::Left), now);
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Right), now)),
+                   vec!["sequence.swipe.up.3,swipe.left.3", "swipe.right.3"]);
+    }
+
+    #[test]
+    fn apps() {
+        let mut config = config::Config::new();
+        config.merge(config::File::from_str(r#"
+            [firefox.sequence]
+            "swipe.up.3,swipe.left.3" = "ctrl+l"
+        "#, config::FileFormat::Toml)).unwrap();
+        let actions = GestureActions::new_with_config(config);
+
+        let app = Arc::new(Mutex::new("firefox"));
+        let focused = app.clone();
+        let mut matcher = SequenceMatcher::new(Box::new(move || {
+            Sequences::from_config(&actions, Some(*focused.lock().unwrap()))
+        }));
+        let now = Instant::now();
+
+        assert!(matcher.gesture(swipe(SwipeDirection::Up), now).is_empty());
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Left), now)), vec!["sequence.swipe.up.3,swipe.left.3"]);
+
+        // the sequence is not bound in other apps, so their gestures are not held back
+        *app.lock().unwrap() = "gedit";
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Up), now)), vec!["swipe.up.3"]);
+        assert_eq!(configs(matcher.gesture(swipe(SwipeDirection::Left), now)), vec!["swipe.left.3"]);
+    }
+}
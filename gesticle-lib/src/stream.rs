@@ -0,0 +1,177 @@
+use std::io;
+use std::os::unix::io::{AsRawFd, RawFd};
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use async_io::Async;
+use futures_core::Stream;
+use input::Libinput;
+
+use crate::devices::DeviceFilter;
+use crate::error::{Error, Result};
+use crate::events::{open_libinput, InputSettings, RawGestureEvent};
+use crate::gestures::{Gesture, GestureFactory, GestureTriggers};
+
+/// Where a `GestureStream` reads gesture events from, whenever its file descriptor becomes readable
+pub trait EventSource: AsRawFd {
+    /// read what is available on the file descriptor
+    fn dispatch(&mut self) -> io::Result<()>;
+
+    /// the next gesture event read, if there are any left
+    fn next_event(&mut self) -> Option<RawGestureEvent>;
+}
+
+/// The events of the devices of a seat, leaving out the ones the input settings do not allow
+pub struct LibinputSource {
+    libinput: Libinput,
+    devices: DeviceFilter,
+}
+
+impl AsRawFd for LibinputSource {
+    fn as_raw_fd(&self) -> RawFd {
+        self.libinput.as_raw_fd()
+    }
+}
+
+impl EventSource for LibinputSource {
+    fn dispatch(&mut self) -> io::Result<()> {
+        self.libinput.dispatch()
+    }
+
+    fn next_event(&mut self) -> Option<RawGestureEvent> {
+        loop {
+            let event = self.libinput.next()?;
+            if !self.devices.allow(&event) {
+                continue;
+            }
+            if let Some(event) = RawGestureEvent::from_libinput(event) {
+                return Some(event);
+            }
+        }
+    }
+}
+
+/// Identified gestures from gesticle as a `Stream`, reading libinput events whenever its file descriptor
+/// is ready instead of on a thread of its own. Libinput is closed when the stream is dropped.
+///
+/// The libinput context cannot be sent to other threads, so a `GestureStream` reading it is `!Send`: poll it
+/// on the task that created it (ie: with `tokio::task::LocalSet` or `futures::executor::block_on`), it can not
+/// be given to `tokio::spawn`
+pub struct GestureStream<S: EventSource = LibinputSource> {
+    source: Async<S>,
+    factory: GestureFactory,
+}
+
+impl GestureStream {
+    pub fn new(input: &InputSettings, triggers: GestureTriggers) -> Result<GestureStream> {
+        let source = LibinputSource { libinput: open_libinput(input)?, devices: DeviceFilter::new(input.clone()) };
+        GestureStream::from_source(source, triggers)
+    }
+}
+
+impl<S: EventSource> GestureStream<S> {
+    /// A stream of the gestures recognized from the events of `source` (ie: a replayed trace)
+    pub fn from_source(source: S, triggers: GestureTriggers) -> Result<GestureStream<S>> {
+        let source = Async::new(source)
+            .map_err(|e| Error::Io("failed to watch input events".to_owned(), e))?;
+        Ok(GestureStream {
+            source,
+            factory: GestureFactory::new(triggers),
+        })
+    }
+}
+
+impl<S: EventSource> Stream for GestureStream<S> {
+    type Item = Gesture;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Gesture>> {
+        let this = self.get_mut();
+        loop {
+            while let Some(event) = this.source.get_mut().next_event() {
+                if let Some(gesture) = this.factory.event(event) {
+                    return Poll::Ready(Some(gesture));
+                }
+            }
+
+            match this.source.poll_readable(cx) {
+                Poll::Ready(Ok(())) => {
+                    if let Err(e) = this.source.get_mut().dispatch() {
+                        error!("failed to read input events: {:?}", e);
+                        return Poll::Ready(None);
+                    }
+                }
+                Poll::Ready(Err(e)) => {
+                    error!("failed to wait for input events: {:?}", e);
+                    return Poll::Ready(None);
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+}
+
+/// Open a stream of identified gestures from gesticle
+pub fn gesture_stream(input: &InputSettings, triggers: GestureTriggers) -> Result<GestureStream> {
+    GestureStream::new(input, triggers)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+    use std::os::unix::io::{AsRawFd, RawFd};
+    use std::sync::mpsc;
+
+    use futures_lite::{future, StreamExt};
+    use nix::fcntl::O_NONBLOCK;
+    use nix::unistd::{pipe2, read, write};
+
+    use crate::configuration::GestureActions;
+    use crate::events::RawGestureEvent;
+    use crate::gestures::GestureTriggers;
+    use crate::stream::{EventSource, GestureStream};
+
+    /// events sent over a channel, readable once a byte is written to the pipe
+    struct ChannelSource {
+        pipe: RawFd,
+        events: mpsc::Receiver<RawGestureEvent>,
+    }
+
+    impl AsRawFd for ChannelSource {
+        fn as_raw_fd(&self) -> RawFd {
+            self.pipe
+        }
+    }
+
+    impl EventSource for ChannelSource {
+        fn dispatch(&mut self) -> io::Result<()> {
+            let mut buf = [0; 16];
+            while read(self.pipe, &mut buf).is_ok_and(|n| n > 0) {}
+            Ok(())
+        }
+
+        fn next_event(&mut self) -> Option<RawGestureEvent> {
+            self.events.try_recv().ok()
+        }
+    }
+
+    #[test]
+    fn stream() {
+        let (pipe, ready) = pipe2(O_NONBLOCK).unwrap();
+        let (tx, events) = mpsc::channel();
+        let triggers = GestureTriggers::from_config(&GestureActions::new_with_config(config::Config::new()));
+        let mut stream = GestureStream::from_source(ChannelSource { pipe, events }, triggers).unwrap();
+
+        // nothing to read yet
+        assert!(future::block_on(future::poll_once(stream.next())).is_none());
+
+        let device = "touchpad".to_owned();
+        tx.send(RawGestureEvent::SwipeBegin { fingers: 3, time: 0, device: device.clone() }).unwrap();
+        tx.send(RawGestureEvent::SwipeUpdate { dx: 0.0, dy: -30.0, time: 10_000, device: device.clone() }).unwrap();
+        tx.send(RawGestureEvent::SwipeUpdate { dx: 0.0, dy: -30.0, time: 20_000, device: device.clone() }).unwrap();
+        tx.send(RawGestureEvent::SwipeEnd { cancelled: false, time: 30_000, device }).unwrap();
+        write(ready, &[1]).unwrap();
+
+        let gesture = future::block_on(stream.next()).unwrap();
+        assert_eq!(gesture.gesture_type.to_config(), "swipe.up.3");
+    }
+}
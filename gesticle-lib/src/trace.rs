@@ -0,0 +1,231 @@
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use serde_json::{json, Value};
+
+use crate::error::{Error, Result};
+use crate::events::RawGestureEvent;
+use crate::gestures::{Decision, Switch};
+
+/// Writes gesture events, and what the recognizer decided on them, to a JSON Lines trace
+pub struct TraceWriter<W: Write> {
+    out: W,
+}
+
+impl TraceWriter<File> {
+    pub fn create<P: AsRef<Path>>(path: P) -> Result<TraceWriter<File>> {
+        let path = path.as_ref();
+        File::create(path)
+            .map(TraceWriter::new)
+            .map_err(|e| Error::Io(format!("failed to create trace {}", path.display()), e))
+    }
+}
+
+impl<W: Write> TraceWriter<W> {
+    pub fn new(out: W) -> TraceWriter<W> {
+        TraceWriter { out }
+    }
+
+    pub fn event(&mut self, event: &RawGestureEvent) {
+        let line = event_to_json(event);
+        self.write(line);
+    }
+
+    /// the decision taken on the gesture ended or triggered by `event`
+    pub fn decision(&mut self, decision: &Decision, event: &RawGestureEvent) {
+        let mut line = json!({"event": "decision", "time": event.time(), "device": event.device()});
+        match decision {
+            Decision::Recognized(t) => line["gesture"] = json!(t.to_config()),
+            Decision::Rejected(reason) => line["rejected"] = json!(reason),
+        }
+        self.write(line);
+    }
+
+    fn write(&mut self, line: Value) {
+        // flush every line so nothing is lost when the daemon is killed
+        if let Err(e) = writeln!(self.out, "{}", line).and_then(|_| self.out.flush()) {
+            error!("failed to write to trace: {:?}", e);
+        }
+    }
+}
+
+/// Read the gesture events of a trace written by a `TraceWriter`
+pub fn read_trace<P: AsRef<Path>>(path: P) -> Result<Vec<RawGestureEvent>> {
+    let path = path.as_ref();
+    let lines = fs::read_to_string(path).map_err(|e| Error::Io(format!("failed to read {}", path.display()), e))?;
+    parse_trace(&lines)
+}
+
+/// Parse the gesture events of a trace, the recorded decisions are left out as they are what replaying
+/// the events decides again
+pub fn parse_trace(lines: &str) -> Result<Vec<RawGestureEvent>> {
+    let mut events = vec![];
+    for (n, line) in lines.lines().enumerate().filter(|(_, l)| !l.trim().is_empty()) {
+        let invalid = |e: String| Error::Trace(format!("line {}: {}", n + 1, e));
+        let value: Value = serde_json::from_str(line).map_err(|e| invalid(e.to_string()))?;
+        if let Some(event) = event_from_json(&value).map_err(invalid)? {
+            events.push(event);
+        }
+    }
+    Ok(events)
+}
+
+fn event_to_json(event: &RawGestureEvent) -> Value {
+    match event {
+        RawGestureEvent::SwipeBegin { fingers, time, device } =>
+            json!({"event": "swipe_begin", "time": time, "device": device, "fingers": fingers}),
+        RawGestureEvent::SwipeUpdate { dx, dy, time, device } =>
+            json!({"event": "swipe_update", "time": time, "device": device, "dx": dx, "dy": dy}),
+        RawGestureEvent::SwipeEnd { cancelled, time, device } =>
+            json!({"event": "swipe_end", "time": time, "device": device, "cancelled": cancelled}),
+        RawGestureEvent::PinchBegin { fingers, scale, time, device } =>
+            json!({"event": "pinch_begin", "time": time, "device": device, "fingers": fingers, "scale": scale}),
+        RawGestureEvent::PinchUpdate { dx, dy, scale, angle, time, device } =>
+            json!({"event": "pinch_update", "time": time, "device": device, "dx": dx, "dy": dy, "scale": scale,
+                   "angle": angle}),
+        RawGestureEvent::PinchEnd { cancelled, scale, time, device } =>
+            json!({"event": "pinch_end", "time": time, "device": device, "cancelled": cancelled, "scale": scale}),
+        RawGestureEvent::HoldBegin { fingers, time, device } =>
+            json!({"event": "hold_begin", "time": time, "device": device, "fingers": fingers}),
+        RawGestureEvent::HoldEnd { cancelled, time, device } =>
+            json!({"event": "hold_end", "time": time, "device": device, "cancelled": cancelled}),
+        RawGestureEvent::TouchDown { slot, x, y, width, height, time, device } =>
+            json!({"event": "touch_down", "time": time, "device": device, "slot": slot, "x": x, "y": y,
+                   "width": width, "height": height}),
+        RawGestureEvent::TouchMotion { slot, x, y, time, device } =>
+            json!({"event": "touch_motion", "time": time, "device": device, "slot": slot, "x": x, "y": y}),
+        RawGestureEvent::TouchUp { slot, time, device } =>
+            json!({"event": "touch_up", "time": time, "device": device, "slot": slot}),
+        RawGestureEvent::TouchCancel { slot, time, device } =>
+            json!({"event": "touch_cancel", "time": time, "device": device, "slot": slot}),
+        RawGestureEvent::PointerButton { button, pressed, time, device } =>
+            json!({"event": "pointer_button", "time": time, "device": device, "button": button, "pressed": pressed}),
+        RawGestureEvent::PointerMotion { dx, dy, time, device } =>
+            json!({"event": "pointer_motion", "time": time, "device": device, "dx": dx, "dy": dy}),
+        RawGestureEvent::Key { key, pressed, time, device } =>
+            json!({"event": "key", "time": time, "device": device, "key": key, "pressed": pressed}),
+        RawGestureEvent::Switch { switch, on, time, device } =>
+            json!({"event": "switch", "time": time, "device": device, "switch": switch.to_string(), "on": on}),
+        RawGestureEvent::PadButton { button, pressed, time, device } =>
+            json!({"event": "pad_button", "time": time, "device": device, "button": button, "pressed": pressed}),
+        RawGestureEvent::PadRing { ring, position, time, device } =>
+            json!({"event": "pad_ring", "time": time, "device": device, "ring": ring, "position": position}),
+        RawGestureEvent::PadStrip { strip, position, time, device } =>
+            json!({"event": "pad_strip", "time": time, "device": device, "strip": strip, "position": position}),
+    }
+}
+
+fn event_from_json(value: &Value) -> std::result::Result<Option<RawGestureEvent>, String> {
+    let field = |name: &str| value.get(name).ok_or_else(|| format!("missing {}", name));
+    let float = |name: &str| field(name).and_then(|v| v.as_f64().ok_or_else(|| format!("{} is not a number", name)));
+
+    let event = field("event")?.as_str().ok_or("event is not a string")?;
+    let time = field("time")?.as_u64().ok_or("time is not a timestamp")?;
+    let device = field("device")?.as_str().ok_or("device is not a string")?.to_owned();
+    let fingers = || field("fingers").and_then(|v| v.as_i64().map(|f| f as i32).ok_or_else(|| "fingers is not a number".to_owned()));
+    let slot = || field("slot").and_then(|v| v.as_u64().map(|s| s as u32).ok_or_else(|| "slot is not a number".to_owned()));
+    let code = |name: &str| field(name).and_then(|v| v.as_u64().map(|c| c as u32).ok_or_else(|| format!("{} is not a number", name)));
+    let pressed = || field("pressed").and_then(|v| v.as_bool().ok_or_else(|| "pressed is not a boolean".to_owned()));
+    let cancelled = || field("cancelled").and_then(|v| v.as_bool().ok_or_else(|| "cancelled is not a boolean".to_owned()));
+
+    let event = match event {
+        "swipe_begin" => RawGestureEvent::SwipeBegin { fingers: fingers()?, time, device },
+        "swipe_update" => RawGestureEvent::SwipeUpdate { dx: float("dx")?, dy: float("dy")?, time, device },
+        "swipe_end" => RawGestureEvent::SwipeEnd { cancelled: cancelled()?, time, device },
+        "pinch_begin" => RawGestureEvent::PinchBegin { fingers: fingers()?, scale: float("scale")?, time, device },
+        "pinch_update" => RawGestureEvent::PinchUpdate {
+            dx: float("dx")?,
+            dy: float("dy")?,
+            scale: float("scale")?,
+            angle: float("angle")?,
+            time,
+            device,
+        },
+        "pinch_end" => RawGestureEvent::PinchEnd { cancelled: cancelled()?, scale: float("scale")?, time, device },
+        "hold_begin" => RawGestureEvent::HoldBegin { fingers: fingers()?, time, device },
+        "hold_end" => RawGestureEvent::HoldEnd { cancelled: cancelled()?, time, device },
+        "touch_down" => RawGestureEvent::TouchDown {
+            slot: slot()?,
+            x: float("x")?,
+            y: float("y")?,
+            width: float("width")?,
+            height: float("height")?,
+            time,
+            device,
+        },
+        "touch_motion" => RawGestureEvent::TouchMotion { slot: slot()?, x: float("x")?, y: float("y")?, time, device },
+        "touch_up" => RawGestureEvent::TouchUp { slot: slot()?, time, device },
+        "touch_cancel" => RawGestureEvent::TouchCancel { slot: slot()?, time, device },
+        "pointer_button" => RawGestureEvent::PointerButton { button: code("button")?, pressed: pressed()?, time, device },
+        "pointer_motion" => RawGestureEvent::PointerMotion { dx: float("dx")?, dy: float("dy")?, time, device },
+        "key" => RawGestureEvent::Key { key: code("key")?, pressed: pressed()?, time, device },
+        "switch" => RawGestureEvent::Switch {
+            switch: match field("switch")?.as_str() {
+                Some("lid") => Switch::Lid,
+                Some("tablet") => Switch::Tablet,
+                _ => return Err("switch is not lid or tablet".to_owned()),
+            },
+            on: field("on")?.as_bool().ok_or("on is not a boolean")?,
+            time,
+            device,
+        },
+        "pad_button" => RawGestureEvent::PadButton { button: code("button")?, pressed: pressed()?, time, device },
+        "pad_ring" => RawGestureEvent::PadRing { ring: code("ring")?, position: float("position")?, time, device },
+        "pad_strip" => RawGestureEvent::PadStrip { strip: code("strip")?, position: float("position")?, time, device },
+        "decision" => return Ok(None),
+        _ => return Err(format!("unknown event {}", event)),
+    };
+    Ok(Some(event))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::configuration::GestureActions;
+    use crate::events::RawGestureEvent;
+    use crate::gestures::{Decision, GestureTriggers, replay};
+    use crate::replay::parse_record;
+    use crate::trace::{parse_trace, TraceWriter};
+
+    #[test]
+    fn record_and_load() {
+        let events = parse_record(include_str!("../tests/traces/synthetic-swipes.yml")).unwrap();
+        let mut out = vec![];
+        {
+            let mut trace = TraceWriter::new(&mut out);
+            for event in &events {
+                trace.event(event);
+            }
+            trace.decision(&Decision::Rejected("unknown direction"), &events[0]);
+        }
+        let lines = String::from_utf8(out).unwrap();
+
+        assert_eq!(lines.lines().count(), events.len() + 1);
+        assert!(lines.lines().last().unwrap().contains("\"rejected\":\"unknown direction\""));
+
+        let loaded = parse_trace(&lines).unwrap();
+        assert_eq!(loaded, events);
+
+        let triggers = GestureTriggers::from_config(&GestureActions::new_with_config(config::Config::new()));
+        let gestures: Vec<String> = replay(loaded, triggers).iter().map(|g| g.gesture_type.to_config()).collect();
+        assert_eq!(gestures, vec!["swipe.left.3", "swipe.up.4", "swipe.downright.3"]);
+
+        let touches = vec![
+            RawGestureEvent::TouchDown { slot: 0, x: 2.0, y: 100.0, width: 300.0, height: 200.0, time: 1, device: "touchscreen".to_owned() },
+            RawGestureEvent::TouchMotion { slot: 0, x: 50.0, y: 100.0, time: 2, device: "touchscreen".to_owned() },
+            RawGestureEvent::TouchUp { slot: 0, time: 3, device: "touchscreen".to_owned() },
+        ];
+        let mut out = vec![];
+        {
+            let mut trace = TraceWriter::new(&mut out);
+            for event in &touches {
+                trace.event(event);
+            }
+        }
+        assert_eq!(parse_trace(&String::from_utf8(out).unwrap()).unwrap(), touches);
+
+        assert!(parse_trace("{\"event\": \"swipe_begin\", \"time\": 1, \"device\": \"touchpad\"}").is_err());
+        assert!(parse_trace("not json").is_err());
+    }
+}
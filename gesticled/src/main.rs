@@ -1,27 +1,36 @@
 extern crate clap;
 extern crate libxdo;
 extern crate libxdo_sys;
+extern crate libc;
 #[macro_use]
 extern crate log;
 extern crate gesticle;
 
 use std::fs;
-use std::os::raw::c_ulong;
+use std::os::raw::{c_int, c_ulong};
 use std::path::Path;
-use std::ptr::null;
+use std::process;
+use std::ptr::{null, null_mut};
 use std::sync::{Arc, Mutex};
 
 use clap::{App, Arg};
 
 use libxdo::XDo;
+use libxdo_sys::{charcodemap_t, CURRENTWINDOW};
+use libxdo_sys::{xdo_clear_active_modifiers, xdo_get_active_modifiers, xdo_set_active_modifiers};
 use libxdo_sys::xdo_free;
 use libxdo_sys::xdo_get_active_window;
 use libxdo_sys::xdo_get_pid_window;
 use libxdo_sys::xdo_new;
 
-use gesticle::gestures::{GestureType, gesture_channel};
+use gesticle::gestures::{Gesture, GestureTriggers, GestureType, gesture_channel, recorded_gesture_channel, replay};
 use gesticle::configuration::{GestureActions, init_logging};
+use gesticle::sequences::{Sequences, sequence_channel};
 use gesticle::dbus;
+use gesticle::devices::list_devices;
+use gesticle::events::InputSettings;
+use gesticle::replay::read_record;
+use gesticle::trace::{TraceWriter, read_trace};
 
 const VERSION: &'static str = env!("CARGO_PKG_VERSION");
 
@@ -37,10 +46,15 @@ impl GestureHandler {
         GestureHandler { xdo, actions }
     }
 
-    fn handle(&self, t: GestureType) {
+    fn handle(&self, gesture: Gesture) {
 
-        let setting = self.context_sensitive_config(t.to_config().as_str()).
-            or_else(|| self.get_setting(t.to_config().as_str()));
+        let Gesture { gesture_type: t, device } = gesture;
+        let setting = match &t {
+            GestureType::Sequence(sequence) => self.sequence_setting(sequence),
+            _ => {
+                self.setting(&t.to_configs(), &device)
+            }
+        };
 
         match setting {
             Some(v) => {
@@ -48,61 +62,98 @@ impl GestureHandler {
                 if v.is_empty() {
                     info!("skipping gesture due to no action: {:?}", t);
                 } else {
-                    self.xdo.send_keysequence(&v, 0).unwrap();
+                    self.send_keysequence(&v);
                 }
             },
             None => warn!("gesture not configured: {:?}", t),
         }
     }
 
-    fn current_window(&self) -> Result<String, String> {
+    /// Sends `keys` with the modifiers held released until they are sent, like `xdotool key --clearmodifiers`,
+    /// otherwise a gesture made holding ctrl and bound to `alt+Left` would send ctrl+alt+Left
+    fn send_keysequence(&self, keys: &str) {
 
         unsafe {
             let xdo = xdo_new(null());
 
             if xdo.is_null() {
-                return Err("Failed to init libxdo.".to_owned());
+                error!("Failed to init libxdo.");
+                return;
             }
 
-            let mut window: c_ulong = 0;
+            let mut modifiers: *mut charcodemap_t = null_mut();
+            let mut count: c_int = 0;
+
+            xdo_get_active_modifiers(xdo, &mut modifiers, &mut count);
+            xdo_clear_active_modifiers(xdo, CURRENTWINDOW, modifiers, count);
 
-            if xdo_get_active_window(xdo, &mut window) != 0 {
-                return Err("Failed to get window id".to_owned());
+            if let Err(e) = self.xdo.send_keysequence(keys, 0) {
+                error!("failed to send {}: {:?}", keys, e);
             }
 
-            let pid = xdo_get_pid_window(xdo, window);
+            xdo_set_active_modifiers(xdo, CURRENTWINDOW, modifiers, count);
 
+            libc::free(modifiers as *mut libc::c_void);
             xdo_free(xdo);
+        }
+    }
 
-            let file = format!("/proc/{}/comm", pid);
+    fn setting(&self, bases: &[String], device: &str) -> Option<String> {
+        let window = active_app();
+        let result = self.actions.lock().unwrap().get_first_for(bases, window.as_deref(), Some(device));
+        debug!("getting setting: {:?} in {:?} on {:?} = {:?}", bases, window, device, result);
+        result
+    }
 
-            match fs::read_to_string(file) {
-                Ok(name) => Ok(name.trim_end().to_owned()),
-                Err(e) => Err(format!("failed to read process name: {:?}", e))
-            }
-        }
+    fn sequence_setting(&self, sequence: &str) -> Option<String> {
+        let window = active_app();
+        let result = self.actions.lock().unwrap().get_sequence_for_app(sequence, window.as_deref());
+        debug!("getting sequence: {:?} in {:?} = {:?}", sequence, window, result);
+        result
     }
 
-    fn context_sensitive_config(&self, base: &str) -> Option<String> {
+}
 
-        match self.current_window() {
-            Ok(window) => {
-                let cfg = format!("{}.{}", window, base);
-                self.get_setting(cfg.as_str())
-            },
-            Err(e) => {
-                error!("could not detect current window: {:?}", e);
-                None
-            }
+fn current_window() -> Result<String, String> {
+
+    unsafe {
+        let xdo = xdo_new(null());
+
+        if xdo.is_null() {
+            return Err("Failed to init libxdo.".to_owned());
         }
-    }
 
-    fn get_setting(&self, setting: &str) -> Option<String> {
-        let result = self.actions.lock().unwrap().get(setting);
-        debug!("getting setting: {:?} = {:?}", setting, result);
-        result
+        let mut window: c_ulong = 0;
+
+        if xdo_get_active_window(xdo, &mut window) != 0 {
+            return Err("Failed to get window id".to_owned());
+        }
+
+        let pid = xdo_get_pid_window(xdo, window);
+
+        xdo_free(xdo);
+
+        let file = format!("/proc/{}/comm", pid);
+
+        match fs::read_to_string(file) {
+            Ok(name) => Ok(name.trim_end().to_owned()),
+            Err(e) => Err(format!("failed to read process name: {:?}", e))
+        }
     }
+}
+
+/// the process name of the active window, which is the app settings are looked up for
+fn active_app() -> Option<String> {
+    current_window().
+        map_err(|e| error!("could not detect current window: {:?}", e)).
+        ok()
+}
 
+/// let the user know why gesticled cannot run
+fn exit_with<T>(e: gesticle::Error) -> T {
+    error!("{}", e);
+    eprintln!("gesticled: {}", e);
+    process::exit(1);
 }
 
 fn main() {
@@ -127,21 +178,84 @@ fn main() {
                 }).
                 help("use specific configuration file")
         ).
+        arg(
+            Arg::with_name("replay").long("replay").
+                value_name("TRACE.yml").
+                conflicts_with("record").
+                help("print the gestures recognized in a `libinput record --with-libinput` trace, or a trace \
+                      written with --record (.jsonl), instead of handling them")
+        ).
+        arg(
+            Arg::with_name("seat").long("seat").
+                value_name("SEAT").
+                help("handle gestures made on the devices of this seat instead of the configured one (seat0)")
+        ).
+        arg(
+            Arg::with_name("record").long("record").
+                value_name("TRACE.jsonl").
+                help("write every gesture event and what was recognized from it to a trace file")
+        ).
+        arg(
+            Arg::with_name("list-devices").long("list-devices").
+                conflicts_with_all(&["replay", "record"]).
+                help("print the input devices of the seat, whether they support gestures and whether they are \
+                      ignored by the input.devices configuration")
+        ).
         get_matches();
 
-    init_logging(args.is_present("debug"), None);
+    init_logging(args.is_present("debug"), None).unwrap_or_else(exit_with);
 
-    let actions = GestureActions::new(args.value_of("config"));
-    let pinch_in_scale_trigger = actions.get_float("gesture.trigger.pinch.in.scale").unwrap_or( 0.0);
-    let pinch_out_scale_trigger = actions.get_float("gesture.trigger.pinch.out.scale").unwrap_or( 0.0);
+    let actions = GestureActions::new(args.value_of("config")).unwrap_or_else(exit_with);
+    let triggers = GestureTriggers::from_config(&actions);
+
+    if let Some(trace) = args.value_of("replay") {
+        let events = if trace.ends_with(".jsonl") {
+            read_trace(trace)
+        } else {
+            read_record(trace)
+        };
+        for gesture in replay(events.unwrap_or_else(exit_with), triggers) {
+            println!("{}", gesture.gesture_type.to_config());
+        }
+        return;
+    }
+
+    let mut input = InputSettings::from_config(&actions);
+    if let Some(seat) = args.value_of("seat") {
+        input.seat = seat.to_owned();
+    }
+
+    if args.is_present("list-devices") {
+        for device in list_devices(&input).unwrap_or_else(exit_with) {
+            if input.allows(&device) {
+                println!("{}", device);
+            } else {
+                println!("{}, ignored", device);
+            }
+        }
+        return;
+    }
 
     let actions_arc = Arc::new(Mutex::new(actions));
 
-    dbus::server(actions_arc.clone());
+    // without a session bus (ie: started before the desktop session) gestures still work, only live reload does not
+    if let Err(e) = dbus::server(actions_arc.clone()) {
+        error!("configuration changes will not be reloaded, failed to start d-bus server: {}", e);
+    }
+
+    // the sequences of the app focused when a sequence is started, read from the configuration as it is reloaded
+    let sequence_actions = actions_arc.clone();
+    let sequences = Box::new(move || Sequences::from_config(&sequence_actions.lock().unwrap(), active_app().as_deref()));
 
     let handler = GestureHandler::new(actions_arc);
 
-    for gesture in gesture_channel(pinch_in_scale_trigger, pinch_out_scale_trigger) {
+    let channel = match args.value_of("record") {
+        Some(path) => TraceWriter::create(path).and_then(|trace| recorded_gesture_channel(input, triggers, trace)),
+        None => gesture_channel(input, triggers),
+    };
+    let (gestures, _channel) = channel.unwrap_or_else(exit_with).split();
+
+    for gesture in sequence_channel(gestures, sequences) {
         debug!("triggered gesture: {:?}", gesture);
         handler.handle(gesture);
     }